@@ -6,13 +6,15 @@ use auxtools::*;
 
 use fxhash::FxBuildHasher;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use parking_lot::{const_rwlock, RwLock};
 
+use rayon::prelude::*;
+
 pub use types::*;
 
-pub use mixture::Mixture;
+pub use mixture::{Mixture, MixtureBuilder};
 
 pub type GasIDX = usize;
 
@@ -31,6 +33,9 @@ static NEXT_GAS_IDS: RwLock<Option<Vec<usize>>> = const_rwlock(None);
 
 static mut REGISTERED_GAS_MIXES: Option<HashSet<u32, FxBuildHasher>> = None;
 
+// Mixes that get topped back up toward a target every tick, by index, along with that target.
+static GAS_SOURCES: RwLock<Option<HashMap<usize, Mixture, FxBuildHasher>>> = const_rwlock(None);
+
 fn is_registered_mix(i: u32) -> bool {
 	unsafe {
 		REGISTERED_GAS_MIXES
@@ -59,6 +64,7 @@ fn unregister_mix(i: u32) {
 fn _init_gas_mixtures() -> Result<(), String> {
 	*GAS_MIXTURES.write() = Some(Vec::with_capacity(240_000));
 	*NEXT_GAS_IDS.write() = Some(Vec::with_capacity(2000));
+	*GAS_SOURCES.write() = Some(HashMap::with_hasher(FxBuildHasher::default()));
 	Ok(())
 }
 
@@ -66,6 +72,7 @@ fn _init_gas_mixtures() -> Result<(), String> {
 fn _shut_down_gases() {
 	*GAS_MIXTURES.write() = None;
 	*NEXT_GAS_IDS.write() = None;
+	*GAS_SOURCES.write() = None;
 	unsafe {
 		REGISTERED_GAS_MIXES
 			.get_or_insert_with(|| HashSet::with_hasher(FxBuildHasher::default()))
@@ -80,6 +87,62 @@ impl GasArena {
 	{
 		f(GAS_MIXTURES.read().as_ref().unwrap())
 	}
+	/// Calls the given closure on every mix that's actually in use, skipping freed ones.
+	pub fn for_each_live<F>(mut f: F)
+	where
+		F: FnMut(usize, &Mixture),
+	{
+		let free: HashSet<usize, FxBuildHasher> = NEXT_GAS_IDS
+			.read()
+			.as_ref()
+			.unwrap()
+			.iter()
+			.copied()
+			.collect();
+		GasArena::with_all_mixtures(|all_mixtures| {
+			for (i, mix) in all_mixtures.iter().enumerate() {
+				if !free.contains(&i) {
+					f(i, &mix.read());
+				}
+			}
+		});
+	}
+	/// How many moles of each gas there are across every mix in use, by gas index.
+	pub fn live_gas_totals() -> Vec<f64> {
+		let free: HashSet<usize, FxBuildHasher> = NEXT_GAS_IDS
+			.read()
+			.as_ref()
+			.unwrap()
+			.iter()
+			.copied()
+			.collect();
+		let num_gases = total_num_gases();
+		GasArena::with_all_mixtures(|all_mixtures| {
+			all_mixtures
+				.par_iter()
+				.enumerate()
+				.with_min_len(1000)
+				.filter(|(i, _)| !free.contains(i))
+				.fold(
+					|| vec![0.0; num_gases],
+					|mut totals, (_, mix)| {
+						for (idx, amt) in mix.read().enumerate() {
+							totals[idx] += amt as f64;
+						}
+						totals
+					},
+				)
+				.reduce(
+					|| vec![0.0; num_gases],
+					|mut totals, other| {
+						for (total, amt) in totals.iter_mut().zip(other) {
+							*total += amt;
+						}
+						totals
+					},
+				)
+		})
+	}
 	fn with_gas_mixture<T, F>(id: usize, f: F) -> Result<T, Runtime>
 	where
 		F: FnOnce(&Mixture) -> Result<T, Runtime>,
@@ -174,6 +237,14 @@ impl GasArena {
 			)
 		}
 	}
+	/// Clears every mixture's cached heat capacity; needed whenever specific heats change.
+	pub fn invalidate_heat_capacities() {
+		GasArena::with_all_mixtures(|all_mixtures| {
+			all_mixtures
+				.par_iter()
+				.for_each(|mix| mix.write().invalidate_heat_capacity());
+		});
+	}
 	/// Fills in the first unused slot in the gas mixtures vector, or adds another one, then sets the argument Value to point to it.
 	pub fn register_mix(mix: &Value) -> DMResult {
 		if NEXT_GAS_IDS.read().as_ref().unwrap().is_empty() {
@@ -226,6 +297,73 @@ impl GasArena {
 		register_mix(mix);
 		Ok(Value::null())
 	}
+	/// Moves every live mix as close to the front of the pool as it'll go, so reading them all is more sequential,
+	/// and points their datums at the new slots. Returns where each moved mix went, since anything else holding mix indices
+	/// needs fixing up too; so don't run this while the atmos thread is running.
+	pub fn defragment() -> HashMap<usize, usize, FxBuildHasher> {
+		let remap = {
+			let mut lock = GAS_MIXTURES.write();
+			let gas_mixtures = lock.as_mut().unwrap();
+			let mut next_lock = NEXT_GAS_IDS.write();
+			let next_gas_ids = next_lock.as_mut().unwrap();
+			let live = gas_mixtures.len() - next_gas_ids.len();
+			let free: HashSet<usize, FxBuildHasher> = next_gas_ids.iter().copied().collect();
+			let mut holes = next_gas_ids
+				.iter()
+				.copied()
+				.filter(|&i| i < live)
+				.collect::<Vec<_>>();
+			holes.sort_unstable();
+			let remap = (live..gas_mixtures.len())
+				.filter(|i| !free.contains(i))
+				.zip(holes)
+				.collect::<HashMap<usize, usize, FxBuildHasher>>();
+			for (&from, &to) in remap.iter() {
+				gas_mixtures.swap(from, to);
+			}
+			// popped from the back, so this hands out the lowest slot first
+			*next_gas_ids = (live..gas_mixtures.len()).rev().collect();
+			remap
+		};
+		if let Some(sources) = GAS_SOURCES.write().as_mut() {
+			*sources = sources
+				.drain()
+				.map(|(idx, target)| (remap.get(&idx).copied().unwrap_or(idx), target))
+				.collect();
+		}
+		if !remap.is_empty() {
+			let registered = unsafe { REGISTERED_GAS_MIXES.as_ref() }
+				.map(|set| set.iter().copied().collect::<Vec<_>>())
+				.unwrap_or_default();
+			for id in registered {
+				use raw_types::values::{ValueData, ValueTag};
+				let mix = unsafe { Value::new(ValueTag::Datum, ValueData { id }) };
+				if let Ok(n) = mix.get_number(byond_string!("_extools_pointer_gasmixture")) {
+					if let Some(&to) = remap.get(&(n.to_bits() as usize)) {
+						let _ = mix.set(
+							byond_string!("_extools_pointer_gasmixture"),
+							f32::from_bits(to as u32),
+						);
+					}
+				}
+			}
+		}
+		remap
+	}
+	/// Checks that the Value's pointer is to a live mix, returning what's wrong with it if it isn't.
+	pub fn check_mix_pointer(mix: &Value) -> Option<String> {
+		let idx = match mix.get_number(byond_string!("_extools_pointer_gasmixture")) {
+			Ok(n) => n.to_bits() as usize,
+			Err(_) => return Some("no gas mixture pointer".to_owned()),
+		};
+		if idx >= GAS_MIXTURES.read().as_ref().unwrap().len() {
+			Some(format!("gas mixture pointer {} is out of range", idx))
+		} else if NEXT_GAS_IDS.read().as_ref().unwrap().contains(&idx) {
+			Some(format!("gas mixture pointer {} points to a freed mix", idx))
+		} else {
+			None
+		}
+	}
 	/// Marks the Value's gas mixture as unused, allowing it to be reallocated to another.
 	pub fn unregister_mix(mix: u32) {
 		if is_registered_mix(mix) {
@@ -250,11 +388,102 @@ impl GasArena {
 						let mut next_gas_ids = NEXT_GAS_IDS.write();
 						next_gas_ids.as_mut().unwrap().push(idx as usize);
 					}
+					// the slot's getting reused, and whatever gets it shouldn't be refilled
+					if let Some(sources) = GAS_SOURCES.write().as_mut() {
+						sources.remove(&(idx as usize));
+					}
 					unregister_mix(mix);
 				}
 			}
 		}
 	}
+	/// Unregisters a whole batch of mixes at once, freeing all their slots under one lock and nulling their pointers.
+	/// Anything that isn't a registered mix, including one that's already been unregistered, is skipped.
+	/// Returns how many were actually unregistered.
+	pub fn unregister_many(mixes: &[Value]) -> usize {
+		let mut freed = Vec::with_capacity(mixes.len());
+		for mix in mixes {
+			if mix.raw.tag != raw_types::values::ValueTag::Datum {
+				continue;
+			}
+			let id = unsafe { mix.raw.data.id };
+			if !is_registered_mix(id) {
+				continue;
+			}
+			if let Ok(n) = mix.get_number(byond_string!("_extools_pointer_gasmixture")) {
+				freed.push(n.to_bits() as usize);
+				let _ = mix.set(byond_string!("_extools_pointer_gasmixture"), Value::null());
+			}
+			unregister_mix(id);
+		}
+		if !freed.is_empty() {
+			NEXT_GAS_IDS
+				.write()
+				.as_mut()
+				.unwrap()
+				.extend(freed.iter().copied());
+			if let Some(sources) = GAS_SOURCES.write().as_mut() {
+				for idx in freed.iter() {
+					sources.remove(idx);
+				}
+			}
+		}
+		freed.len()
+	}
+}
+
+/// Makes a new /datum/gas_mixture of the given volume by calling /proc/__auxtools_new_gas_mixture, which DM needs to
+/// define, and makes sure it actually got registered.
+pub fn new_gas_mixture(volume: f32) -> DMResult {
+	let mix = Proc::find(byond_string!("/proc/__auxtools_new_gas_mixture"))
+		.ok_or_else(|| runtime!("Could not find /proc/__auxtools_new_gas_mixture"))?
+		.call(&[&Value::from(volume)])?;
+	if let Some(problem) = GasArena::check_mix_pointer(&mix) {
+		return Err(runtime!("New gas mixture wasn't registered: {}", problem));
+	}
+	Ok(mix)
+}
+
+/// Has the mix refilled toward `target` every tick from now on, replacing any target it had before.
+pub fn register_gas_source(mix: &Value, target: Mixture) -> Result<(), Runtime> {
+	let idx = mix
+		.get_number(byond_string!("_extools_pointer_gasmixture"))
+		.map_err(|_| {
+			runtime!(
+				"Attempt to interpret non-number value as number {} {}:{}",
+				std::file!(),
+				std::line!(),
+				std::column!()
+			)
+		})?
+		.to_bits() as usize;
+	GAS_SOURCES.write().as_mut().unwrap().insert(idx, target);
+	Ok(())
+}
+
+/// Stops refilling the mix, returning whether it was a source at all.
+pub fn unregister_gas_source(mix: &Value) -> bool {
+	mix.get_number(byond_string!("_extools_pointer_gasmixture"))
+		.map_or(false, |n| {
+			GAS_SOURCES
+				.write()
+				.as_mut()
+				.unwrap()
+				.remove(&(n.to_bits() as usize))
+				.is_some()
+		})
+}
+
+/// Moves every source mix `rate` of the way back toward its target.
+pub(crate) fn refill_gas_sources(rate: f32) {
+	let sources = GAS_SOURCES.read();
+	GasArena::with_all_mixtures(|all_mixtures| {
+		for (&idx, target) in sources.as_ref().unwrap().iter() {
+			if let Some(mix) = all_mixtures.get(idx) {
+				mix.write().refill_toward(target, rate);
+			}
+		}
+	});
 }
 
 /// Gets the mix for the given value, and calls the provided closure with a reference to that mix as an argument.
@@ -400,3 +629,10 @@ pub(crate) fn amt_gases() -> usize {
 pub(crate) fn tot_gases() -> usize {
 	GAS_MIXTURES.read().as_ref().unwrap().len()
 }
+
+/// How many more mixtures can be registered before the pool has to reallocate.
+pub(crate) fn gas_pool_headroom() -> usize {
+	let gas_mixtures = GAS_MIXTURES.read();
+	let gas_mixtures = gas_mixtures.as_ref().unwrap();
+	gas_mixtures.capacity() - gas_mixtures.len() + NEXT_GAS_IDS.read().as_ref().unwrap().len()
+}
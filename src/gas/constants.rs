@@ -10,6 +10,8 @@ pub const TCRYO: f32 = 225.0;
 pub const T0C: f32 = 273.15;
 ///  20degC
 pub const T20C: f32 = 293.15;
+/// Cp/Cv of a diatomic ideal gas, what gases without a heat capacity ratio of their own are taken to be
+pub const DIATOMIC_HEAT_CAPACITY_RATIO: f32 = 1.4;
 /// Amount of gas below which any amounts will be truncated to 0.
 pub const GAS_MIN_MOLES: f32 = 0.0001;
 /// Heat capacities below which heat will be considered 0.
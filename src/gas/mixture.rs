@@ -14,7 +14,9 @@ use tinyvec::TinyVec;
 use crate::reaction::ReactionIdentifier;
 
 use super::{
-	constants::*, gas_visibility, total_num_gases, with_reactions, with_specific_heats, GasIDX,
+	constants::*, gas_idx_from_string, gas_idx_to_id, gas_visibility, total_num_gases,
+	with_danger_thresholds, with_gas_categories, with_heat_capacity_ratios, with_molar_masses,
+	with_reactions, with_specific_heats, GasIDX,
 };
 
 type SpecificFireInfo = (usize, f32, f32);
@@ -41,13 +43,15 @@ pub struct Mixture {
 	pub volume: f32,
 	min_heat_capacity: f32,
 	immutable: bool,
+	reactions_suppressed: bool,
 	moles: TinyVec<[f32; 8]>,
 	cached_heat_capacity: Cell<Option<f32>>,
+	cached_total_moles: Cell<Option<f32>>,
 	cached_vis_hash: VisHash,
 }
 
 /*
-	Cell is not thread-safe. However, we use it only for caching heat capacity. The worst case race condition
+	Cell is not thread-safe. However, we use it only for caching heat capacity and total moles. The worst case race condition
 	is thus thread A and B try to access heat capacity at the same time; both find that it's currently
 	uncached, so both go to calculate it; both calculate it, and both calculate it to the same value,
 	then one sets the cache to that value, then the other does.
@@ -70,11 +74,13 @@ impl Mixture {
 	pub fn new() -> Self {
 		Self {
 			moles: TinyVec::new(),
-			temperature: 2.7,
+			temperature: TCMB,
 			volume: 2500.0,
 			min_heat_capacity: 0.0,
 			immutable: false,
+			reactions_suppressed: false,
 			cached_heat_capacity: Cell::new(None),
+			cached_total_moles: Cell::new(None),
 			cached_vis_hash: VisHash(AtomicU64::new(0)),
 		}
 	}
@@ -88,10 +94,38 @@ impl Mixture {
 	pub fn is_corrupt(&self) -> bool {
 		!self.temperature.is_normal() || self.moles.len() > total_num_gases()
 	}
+	/// What's wrong with the mix, if anything: whatever `is_corrupt` would catch, plus bad mole amounts
+	/// and more moles in total than the given cap.
+	pub fn audit(&self, max_moles: f32) -> Option<&'static str> {
+		if !self.temperature.is_finite() {
+			Some("non-finite temperature")
+		} else if self.moles.iter().any(|amt| !amt.is_finite()) {
+			Some("non-finite moles")
+		} else if self.moles.iter().any(|&amt| amt < 0.0) {
+			Some("negative moles")
+		} else if self.is_corrupt() {
+			Some("corrupt")
+		} else if self.total_moles() > max_moles {
+			Some("too many moles")
+		} else {
+			None
+		}
+	}
+	/// Empties the mix out, keeping its volume, if `audit` finds its temperature or moles broken.
+	/// Immutable mixes are left be. Returns whether it did anything.
+	pub fn reset_if_corrupt(&mut self) -> bool {
+		if self.immutable || self.audit(f32::INFINITY).is_none() {
+			return false;
+		}
+		self.moles.clear();
+		self.temperature = TCMB;
+		self.moles_changed();
+		true
+	}
 	/// Fixes any corruption found.
 	pub fn fix_corruption(&mut self) {
 		self.garbage_collect();
-		if self.temperature < 2.7 || !self.temperature.is_normal() {
+		if self.temperature < TCMB || !self.temperature.is_normal() {
 			self.set_temperature(293.15);
 		}
 	}
@@ -99,10 +133,11 @@ impl Mixture {
 	pub fn get_temperature(&self) -> f32 {
 		self.temperature
 	}
-	/// Sets the temperature, if the mix isn't immutable. T
+	/// Sets the temperature, if the mix isn't immutable. Never goes below TCMB, zero included; everything
+	/// that cools a mix down goes through here, so nothing else needs to clamp. NaN and infinities are ignored. T
 	pub fn set_temperature(&mut self, temp: f32) {
-		if !self.immutable && temp.is_normal() {
-			self.temperature = temp;
+		if !self.immutable && temp.is_finite() {
+			self.temperature = temp.max(TCMB);
 		}
 	}
 	/// Sets the minimum heat capacity of this mix.
@@ -131,6 +166,24 @@ impl Mixture {
 	pub fn mark_immutable(&mut self) {
 		self.immutable = true;
 	}
+	/// Empties the mix and makes it immutable, so anything merged into it is just gone and its pressure's
+	/// always zero. For vents to space and the like, that need somewhere to dump gas.
+	pub fn make_vacuum_sink(&mut self) {
+		self.clear_with_vol(self.volume);
+		self.mark_immutable();
+	}
+	/// Lets the mix be changed again.
+	pub fn unmark_immutable(&mut self) {
+		self.immutable = false;
+	}
+	/// Stops the mix from reacting at all, or lets it react again, regardless of what's in it.
+	pub fn set_reactions_suppressed(&mut self, suppressed: bool) {
+		self.reactions_suppressed = suppressed;
+	}
+	/// Returns whether this gas mixture has had its reactions suppressed.
+	pub fn reactions_suppressed(&self) -> bool {
+		self.reactions_suppressed
+	}
 	/// Returns whether this gas mixture is immutable.
 	pub fn is_immutable(&self) -> bool {
 		self.immutable
@@ -150,7 +203,7 @@ impl Mixture {
 			unsafe {
 				*self.moles.get_unchecked_mut(idx) = amt;
 			};
-			self.cached_heat_capacity.set(None);
+			self.moles_changed();
 		}
 	}
 	pub fn adjust_moles(&mut self, idx: GasIDX, amt: f32) {
@@ -161,7 +214,7 @@ impl Mixture {
 			if amt < 0.0 {
 				self.garbage_collect();
 			}
-			self.cached_heat_capacity.set(None);
+			self.moles_changed();
 		}
 	}
 	#[inline(never)] // mostly this makes it so that heat_capacity itself is inlined
@@ -184,6 +237,15 @@ impl Mixture {
 			.filter(|cap| cap.is_finite() && cap.is_sign_positive())
 			.unwrap_or_else(|| self.slow_heat_capacity())
 	}
+	/// Forgets the cached heat capacity, so it'll be recalculated next time it's needed.
+	pub fn invalidate_heat_capacity(&self) {
+		self.cached_heat_capacity.set(None);
+	}
+	// Anything that changes gas amounts needs to call this.
+	fn moles_changed(&self) {
+		self.cached_heat_capacity.set(None);
+		self.cached_total_moles.set(None);
+	}
 	/// Heat capacity of exactly one gas in this mix.
 	pub fn partial_heat_capacity(&self, idx: GasIDX) -> f32 {
 		self.moles
@@ -192,17 +254,205 @@ impl Mixture {
 			.map_or(0.0, |amt| amt * with_specific_heats(|heats| heats[idx]))
 	}
 	/// The total mole count of the mixture. Moles.
+	/// Cached, since consoles and such ask for pressure far more often than the gas changes.
 	pub fn total_moles(&self) -> f32 {
-		self.moles.iter().sum()
+		self.cached_total_moles.get().unwrap_or_else(|| {
+			let total = self.moles.iter().sum();
+			self.cached_total_moles.set(Some(total));
+			total
+		})
 	}
 	/// Pressure. Kilopascals.
 	pub fn return_pressure(&self) -> f32 {
 		self.total_moles() * R_IDEAL_GAS_EQUATION * self.temperature / self.volume
 	}
+	/// How many moles would have to be removed, at the current temperature and volume, to bring the pressure
+	/// to the target in kilopascals. Negative if it'd take adding gas instead; zero if the mix has no volume.
+	pub fn moles_to_target_pressure(&self, target_pressure: f32) -> f32 {
+		if self.volume > 0.0 && self.temperature > 0.0 {
+			self.total_moles()
+				- target_pressure.max(0.0) * self.volume / (R_IDEAL_GAS_EQUATION * self.temperature)
+		} else {
+			0.0
+		}
+	}
+	/// What the pressure would be at the given temperature, with the same moles and volume. Kilopascals.
+	/// Zero-volume mixes have no meaningful pressure, so that's 0.
+	pub fn pressure_at_temperature(&self, temp: f32) -> f32 {
+		if self.volume > 0.0 {
+			self.total_moles() * R_IDEAL_GAS_EQUATION * temp / self.volume
+		} else {
+			0.0
+		}
+	}
+	/// Partial pressure of one gas in this mix. Kilopascals. Volumeless mixes are 0.
+	pub fn partial_pressure(&self, idx: GasIDX) -> f32 {
+		if self.volume > 0.0 {
+			self.get_moles(idx) * R_IDEAL_GAS_EQUATION * self.temperature / self.volume
+		} else {
+			0.0
+		}
+	}
+	/// What fraction of the mix's moles are the given gas. Empty mixes are 0.
+	pub fn gas_fraction(&self, idx: GasIDX) -> f32 {
+		let total = self.total_moles();
+		if total > 0.0 {
+			self.get_moles(idx) / total
+		} else {
+			0.0
+		}
+	}
+	/// Whether the given gas makes up at least `purity` of the mix.
+	pub fn is_pure(&self, idx: GasIDX, purity: f32) -> bool {
+		self.total_moles() > 0.0 && self.gas_fraction(idx) >= purity
+	}
+	/// Total moles of every gas in any of the given categories.
+	pub fn category_moles(&self, category: u32) -> f32 {
+		with_gas_categories(|categories| {
+			self.enumerate()
+				.filter(|&(i, _)| categories.get(i).map_or(false, |cat| cat & category != 0))
+				.map(|(_, amt)| amt)
+				.sum()
+		})
+	}
+	/// The most dangerous gas in the mix and how many times over its danger threshold it is,
+	/// or None if there's no dangerous gas in it at all.
+	pub fn hazard_level(&self) -> Option<(GasIDX, f32)> {
+		with_danger_thresholds(|thresholds| {
+			self.enumerate()
+				.filter(|&(_, amt)| amt > 0.0)
+				.filter_map(|(i, amt)| {
+					thresholds
+						.get(i)
+						.copied()
+						.flatten()
+						.map(|threshold| (i, amt / threshold))
+				})
+				.max_by(|&(_, a), &(_, b)| float_ord::FloatOrd(a).cmp(&float_ord::FloatOrd(b)))
+		})
+	}
+	/// The mole-weighted average molar mass of the mix, in grams per mole. Zero if the mix is empty.
+	pub fn molar_mass(&self) -> f32 {
+		let total_moles = self.total_moles();
+		if total_moles <= 0.0 {
+			return 0.0;
+		}
+		with_molar_masses(|masses| {
+			self.enumerate()
+				.map(|(i, amt)| amt * masses.get(i).copied().unwrap_or(0.0))
+				.sum::<f32>()
+		}) / total_moles
+	}
+	/// The mix's ratio of specific heats, Cp/Cv, from each gas's own. The constant-volume specific heats
+	/// are what add up by moles, so it's gotten from those. Empty mixes count as diatomic.
+	pub fn gamma(&self) -> f32 {
+		let total_moles = self.total_moles();
+		if total_moles <= 0.0 {
+			return DIATOMIC_HEAT_CAPACITY_RATIO;
+		}
+		// Cv of each gas, in units of R
+		let heat_capacity_over_r: f32 = with_heat_capacity_ratios(|ratios| {
+			self.enumerate()
+				.map(|(i, amt)| {
+					amt / (ratios
+						.get(i)
+						.copied()
+						.unwrap_or(DIATOMIC_HEAT_CAPACITY_RATIO)
+						- 1.0)
+				})
+				.sum()
+		});
+		1.0 + total_moles / heat_capacity_over_r
+	}
+	/// Every gas with more than trace amounts and its partial pressure, highest pressure first.
+	pub fn gases_by_pressure(&self) -> Vec<(GasIDX, f32)> {
+		let mut gases = self
+			.enumerate()
+			.filter(|&(_, amt)| amt > GAS_MIN_MOLES)
+			.map(|(i, _)| (i, self.partial_pressure(i)))
+			.collect::<Vec<_>>();
+		gases.sort_by(|&(i, a), &(j, b)| {
+			float_ord::FloatOrd(b)
+				.cmp(&float_ord::FloatOrd(a))
+				.then(i.cmp(&j))
+		});
+		gases
+	}
+	/// Writes the mix out as `temperature;volume;id=moles;...`. Gases are stored by id rather than index,
+	/// so a save stays good if gases get added or reordered before it's loaded.
+	pub fn to_save_string(&self) -> Result<String, auxtools::Runtime> {
+		let mut saved = format!("{};{}", self.temperature, self.volume);
+		for (idx, amt) in self.enumerate().filter(|&(_, amt)| amt > 0.0) {
+			saved.push_str(&format!(";{}={}", &*gas_idx_to_id(idx)?, amt));
+		}
+		Ok(saved)
+	}
+	/// Reads back a mix written by `to_save_string`, along with the ids of any gases in it
+	/// that don't exist anymore, which are left out.
+	pub fn from_save_string(saved: &str) -> Result<(Self, Vec<String>), auxtools::Runtime> {
+		let malformed = || auxtools::runtime!("Malformed gas mixture save: {}", saved);
+		let mut fields = saved.split(';');
+		let number = |field: Option<&str>| -> Result<f32, auxtools::Runtime> {
+			field.and_then(|f| f.parse().ok()).ok_or_else(malformed)
+		};
+		let temperature = number(fields.next())?;
+		let volume = number(fields.next())?;
+		let mut mix = Self::from_vol(volume);
+		let mut dropped = Vec::new();
+		for field in fields {
+			let (id, amt) = field.split_once('=').ok_or_else(malformed)?;
+			let amt = number(Some(amt))?;
+			match gas_idx_from_string(id) {
+				Ok(idx) => mix.set_moles(idx, amt),
+				Err(_) => dropped.push(id.to_string()),
+			}
+		}
+		mix.set_temperature(temperature);
+		Ok((mix, dropped))
+	}
+	/// Moles per liter. Zero if the mix has no volume.
+	pub fn density(&self) -> f32 {
+		if self.volume > 0.0 {
+			self.total_moles() / self.volume
+		} else {
+			0.0
+		}
+	}
+	/// Scales every gas so the mix has the given density, keeping composition and volume.
+	/// Does nothing to empty or volumeless mixes, since there's nothing to scale.
+	pub fn set_density(&mut self, density: f32) {
+		let cur_density = self.density();
+		if cur_density > 0.0 && density >= 0.0 {
+			self.multiply(density / cur_density);
+		}
+	}
+	/// Changes the volume without letting any heat in or out, so compressing heats the gas up.
+	/// Goes by the mix's own gamma, so it keeps pressure times volume to the gamma constant.
+	pub fn adiabatic_scale_volume(&mut self, new_volume: f32) {
+		if self.immutable || !new_volume.is_normal() || new_volume < 0.0 {
+			return;
+		}
+		if self.heat_capacity() > MINIMUM_HEAT_CAPACITY && self.volume > 0.0 {
+			self.set_temperature(
+				self.temperature * (self.volume / new_volume).powf(self.gamma() - 1.0),
+			);
+		}
+		self.volume = new_volume;
+	}
 	/// Thermal energy. Joules?
 	pub fn thermal_energy(&self) -> f32 {
 		self.heat_capacity() * self.temperature
 	}
+	/// Thermal energy over what the mix would have at the reference temperature; negative if it's colder.
+	pub fn internal_energy(&self, reference_temperature: f32) -> f32 {
+		self.heat_capacity() * (self.temperature - reference_temperature)
+	}
+	/// Sets the temperature to whatever gives the mix this much thermal energy. Does nothing to empty mixes.
+	pub fn set_thermal_energy(&mut self, energy: f32) {
+		if self.total_moles() > 0.0 {
+			self.set_temperature(energy / self.heat_capacity());
+		}
+	}
 	/// Merges one gas mixture into another.
 	pub fn merge(&mut self, giver: &Self) {
 		if self.immutable {
@@ -222,6 +472,19 @@ impl Mixture {
 			);
 		}
 		self.cached_heat_capacity.set(Some(combined_heat_capacity));
+		self.cached_total_moles.set(None);
+	}
+	/// What the temperature would be after merging the giver in, without merging anything.
+	pub fn temperature_after_merge(&self, giver: &Self) -> f32 {
+		let our_heat_capacity = self.heat_capacity();
+		let other_heat_capacity = giver.heat_capacity();
+		let combined_heat_capacity = our_heat_capacity + other_heat_capacity;
+		if combined_heat_capacity > MINIMUM_HEAT_CAPACITY {
+			(our_heat_capacity * self.temperature + other_heat_capacity * giver.temperature)
+				/ combined_heat_capacity
+		} else {
+			self.temperature
+		}
 	}
 	/// Transfers only the given gases from us to another mix.
 	pub fn transfer_gases_to(&mut self, r: f32, gases: &[GasIDX], into: &mut Self) {
@@ -238,8 +501,8 @@ impl Mixture {
 				}
 			}
 		});
-		self.cached_heat_capacity.set(None);
-		into.cached_heat_capacity.set(None);
+		self.moles_changed();
+		into.moles_changed();
 		into.set_temperature((initial_energy + heat_transfer) / into.heat_capacity());
 	}
 	/// Takes a percentage of this gas mixture's moles and puts it into another mixture. if this mix is mutable, also removes those moles from the original.
@@ -271,6 +534,159 @@ impl Mixture {
 	pub fn remove(&mut self, amount: f32) -> Self {
 		self.remove_ratio(amount / self.total_moles())
 	}
+	/// Splits what it'd take to turn this mix into `target` into gas to add and gas to remove.
+	/// Gas to add is at the target's temperature; gas to remove is at ours.
+	pub fn difference_into(&self, target: &Self, to_add: &mut Self, to_remove: &mut Self) {
+		to_add.clear();
+		to_remove.clear();
+		for idx in 0..self.moles.len().max(target.moles.len()) {
+			let delta = target.get_moles(idx) - self.get_moles(idx);
+			if delta > 0.0 {
+				to_add.set_moles(idx, delta);
+			} else if delta < 0.0 {
+				to_remove.set_moles(idx, -delta);
+			}
+		}
+		to_add.set_temperature(target.temperature);
+		to_remove.set_temperature(self.temperature);
+	}
+	/// Leaks gas into another mix through a small opening, returning how many moles got through.
+	/// A conductance of 1 moves enough to equalize pressure; gas leaked into immutable mixes is lost.
+	pub fn leak_into(&mut self, into: &mut Self, conductance: f32) -> f32 {
+		let pressure_delta = self.return_pressure() - into.return_pressure();
+		if pressure_delta <= 0.0 || self.temperature <= 0.0 || self.volume <= 0.0 {
+			return 0.0;
+		}
+		let share = if into.immutable {
+			1.0
+		} else {
+			into.volume / (self.volume + into.volume)
+		};
+		let moles = (conductance.clamp(0.0, 1.0) * share * pressure_delta * self.volume
+			/ (R_IDEAL_GAS_EQUATION * self.temperature))
+			.min(self.total_moles());
+		let leaked = self.remove(moles);
+		into.merge(&leaked);
+		moles
+	}
+	/// A one-way valve: leaks into the other mix like `leak_into`, but only while this side is at the higher pressure.
+	/// Returns the moles moved, which is zero if the pressure's the other way around.
+	pub fn valve_into(&mut self, into: &mut Self, conductance: f32) -> f32 {
+		if self.return_pressure() > into.return_pressure() {
+			self.leak_into(into, conductance)
+		} else {
+			0.0
+		}
+	}
+	/// Dumps gas out into space, or anything else at `space_pressure` that can take it all, through an orifice.
+	/// Flow goes with the pressure difference until the outside pressure drops below the critical ratio for this mix's
+	/// gamma; past that the flow's choked, and it's the same however low the outside gets. Returns the moles vented.
+	pub fn vent_to_space(&mut self, conductance: f32, space_pressure: f32) -> f32 {
+		let pressure = self.return_pressure();
+		if self.immutable
+			|| pressure <= space_pressure
+			|| self.temperature <= 0.0
+			|| self.volume <= 0.0
+		{
+			return 0.0;
+		}
+		let gamma = self.gamma();
+		let critical_ratio = (2.0 / (gamma + 1.0)).powf(gamma / (gamma - 1.0));
+		let pressure_delta = pressure - space_pressure.max(pressure * critical_ratio);
+		let moles = (conductance.clamp(0.0, 1.0) * pressure_delta * self.volume
+			/ (R_IDEAL_GAS_EQUATION * self.temperature))
+			.min(self.total_moles());
+		self.remove(moles);
+		moles
+	}
+	/// Moves the mix `rate` of the way toward the target's moles and temperature; a rate of 1 copies it outright.
+	pub fn refill_toward(&mut self, target: &Self, rate: f32) {
+		if self.immutable {
+			return;
+		}
+		let rate = rate.clamp(0.0, 1.0);
+		for idx in 0..self.moles.len().max(target.moles.len()) {
+			let amt = self.get_moles(idx);
+			self.set_moles(idx, amt + (target.get_moles(idx) - amt) * rate);
+		}
+		self.garbage_collect();
+		self.set_temperature(self.temperature + (target.temperature - self.temperature) * rate);
+	}
+	/// Like `refill_toward`, but with `conserve_moles` set, goes toward the target's composition scaled to
+	/// however many moles the mix has now, so only what's in it changes, not how much. An empty target has
+	/// no composition to go toward, so that does nothing.
+	pub fn blend_toward(&mut self, target: &Self, rate: f32, conserve_moles: bool) {
+		if !conserve_moles {
+			self.refill_toward(target, rate);
+			return;
+		}
+		let target_moles = target.total_moles();
+		if target_moles <= 0.0 {
+			return;
+		}
+		let mut recipe = target.clone();
+		recipe.unmark_immutable();
+		recipe.multiply(self.total_moles() / target_moles);
+		self.refill_toward(&recipe, rate);
+	}
+	/// Pools both mixes' gas and heat, then splits it back up by volume, so both end up the same.
+	pub fn equalize_into(&mut self, other: &mut Self) {
+		let total_volume = self.volume + other.volume;
+		if total_volume <= 0.0 {
+			return;
+		}
+		let mut total = self.clone();
+		total.merge(other);
+		let (our_vol, other_vol) = (self.volume, other.volume);
+		self.copy_from_mutable(&total);
+		self.multiply(our_vol / total_volume);
+		other.copy_from_mutable(&total);
+		other.multiply(other_vol / total_volume);
+	}
+	/// Like `remove`, but faster-diffusing gases make up more of what's removed.
+	/// Each gas's share is weighted by its amount times its coefficient.
+	/// Immutable mixes hand out the same split without losing anything, same as `remove`.
+	pub fn remove_by_diffusion(&mut self, amount: f32, coefficients: &[f32]) -> Self {
+		let mut removed = Self::from_vol(self.volume);
+		if amount <= 0.0 {
+			return removed;
+		}
+		let total_weight: f32 = self
+			.enumerate()
+			.map(|(i, amt)| amt * coefficients.get(i).copied().unwrap_or(1.0))
+			.sum();
+		if total_weight <= 0.0 {
+			return removed;
+		}
+		for i in 0..self.moles.len() {
+			let amt = self.moles[i];
+			let weight = amt * coefficients.get(i).copied().unwrap_or(1.0);
+			let delta = (amount * weight / total_weight).min(amt);
+			if !self.immutable {
+				self.moles[i] -= delta;
+			}
+			removed.set_moles(i, delta);
+		}
+		removed.temperature = self.temperature;
+		if !self.immutable {
+			self.garbage_collect();
+		}
+		removed
+	}
+	/// Trades gas and temperature with another mix, and volume too unless told to keep it.
+	/// Does nothing if either is immutable.
+	pub fn swap_contents(&mut self, other: &mut Self, keep_volume: bool) {
+		if self.immutable || other.immutable {
+			return;
+		}
+		std::mem::swap(&mut self.moles, &mut other.moles);
+		std::mem::swap(&mut self.temperature, &mut other.temperature);
+		self.cached_heat_capacity.swap(&other.cached_heat_capacity);
+		self.cached_total_moles.swap(&other.cached_total_moles);
+		if !keep_volume {
+			std::mem::swap(&mut self.volume, &mut other.volume);
+		}
+	}
 	/// Copies from a given gas mixture, if we're mutable.
 	pub fn copy_from_mutable(&mut self, sample: &Self) {
 		if self.immutable {
@@ -280,6 +696,7 @@ impl Mixture {
 		self.temperature = sample.temperature;
 		self.cached_heat_capacity
 			.set(sample.cached_heat_capacity.get());
+		self.cached_total_moles.set(sample.cached_total_moles.get());
 	}
 	/// A very simple finite difference solution to the heat transfer equation.
 	/// Works well enough for our purposes, though perhaps called less often
@@ -365,15 +782,16 @@ impl Mixture {
 	pub fn clear(&mut self) {
 		if !self.immutable {
 			self.moles.clear();
-			self.cached_heat_capacity.set(None);
+			self.moles_changed();
 		}
 	}
 	/// Resets the gas mixture to an initialized-with-volume state.
 	pub fn clear_with_vol(&mut self, vol: f32) {
-		self.temperature = 2.7;
+		self.temperature = TCMB;
 		self.volume = vol;
 		self.min_heat_capacity = 0.0;
 		self.immutable = false;
+		self.reactions_suppressed = false;
 		self.clear();
 	}
 	/// Multiplies every gas molage with this value.
@@ -388,10 +806,14 @@ impl Mixture {
 	}
 	/// Checks if the proc can react with any reactions.
 	pub fn can_react(&self) -> bool {
-		with_reactions(|reactions| reactions.iter().any(|r| r.check_conditions(self)))
+		!self.reactions_suppressed
+			&& with_reactions(|reactions| reactions.iter().any(|r| r.check_conditions(self)))
 	}
 	/// Gets all of the reactions this mix should do.
 	pub fn all_reactable(&self) -> Vec<ReactionIdentifier> {
+		if self.reactions_suppressed {
+			return Vec::new();
+		}
 		with_reactions(|reactions| {
 			reactions
 				.iter()
@@ -399,6 +821,30 @@ impl Mixture {
 				.collect()
 		})
 	}
+	/// Checks if any of the loaded fire reactions could happen in this mix right now.
+	pub fn is_combustible(&self) -> bool {
+		with_reactions(|reactions| {
+			reactions
+				.iter()
+				.any(|r| r.is_fire() && r.check_conditions(self))
+		})
+	}
+	/// Moles of the most plentiful fuel over moles of the most plentiful oxidizer, going by each gas's fire info
+	/// rather than temperature, so it's meaningful before anything's lit. None if there's no fuel or no oxidizer.
+	pub fn combustion_ratio(&self) -> Option<f32> {
+		use crate::types::FireInfo;
+		let (fuel, oxidizer) = super::with_gas_info(|gas_info| {
+			self.moles.iter().zip(gas_info).fold(
+				(0.0_f32, 0.0_f32),
+				|(fuel, oxidizer), (&amt, this_gas_info)| match this_gas_info.fire_info {
+					FireInfo::Fuel(_) => (fuel.max(amt), oxidizer),
+					FireInfo::Oxidation(_) => (fuel, oxidizer.max(amt)),
+					FireInfo::None => (fuel, oxidizer),
+				},
+			)
+		});
+		(fuel > GAS_MIN_MOLES && oxidizer > GAS_MIN_MOLES).then(|| fuel / oxidizer)
+	}
 	/// Returns a tuple with oxidation power and fuel amount of this gas mixture.
 	pub fn get_burnability(&self) -> (f32, f32) {
 		use crate::types::FireInfo;
@@ -489,6 +935,13 @@ impl Mixture {
 		self.enumerate()
 			.any(|(i, gas)| gas_visibility(i as usize).map_or(false, |amt| gas >= amt))
 	}
+	/// Every gas there's enough of to be seen.
+	pub fn visible_gases(&self) -> Vec<GasIDX> {
+		self.enumerate()
+			.filter(|&(i, gas)| gas_visibility(i).map_or(false, |amt| gas >= amt))
+			.map(|(i, _)| i)
+			.collect()
+	}
 	/// A hashed representation of the visibility of a gas, so that it only needs to update vis when actually changed.
 	pub fn vis_hash_changed(&self, gas_visibility: &[Option<f32>]) -> bool {
 		use std::hash::Hasher;
@@ -503,6 +956,17 @@ impl Mixture {
 		let cur_hash = hasher.finish();
 		self.cached_vis_hash.0.swap(cur_hash, Relaxed) != cur_hash
 	}
+	/// A hash of exactly how much of each gas is in the mix, ignoring temperature and volume.
+	/// Gases with no moles don't count, so trimming zeroes off doesn't change it.
+	pub fn composition_hash(&self) -> u64 {
+		use std::hash::Hasher;
+		let mut hasher: fxhash::FxHasher64 = fxhash::FxHasher64::default();
+		for (i, gas) in self.enumerate().filter(|&(_, gas)| gas != 0.0) {
+			hasher.write_usize(i);
+			hasher.write_u32(gas.to_bits());
+		}
+		hasher.finish()
+	}
 	// Removes all redundant zeroes from the gas mixture.
 	pub fn garbage_collect(&mut self) {
 		let mut last_valid_found = 0;
@@ -514,10 +978,39 @@ impl Mixture {
 			}
 		}
 		self.moles.truncate(last_valid_found + 1);
+		self.moles_changed();
 	}
 }
 
-use std::ops::{Add, Mul};
+/// A mix's gas amounts and temperature rounded off into buckets, so that near-identical mixes compare equal.
+/// Meant for use as a cache key.
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub struct QuantizedMixture {
+	moles: Vec<u32>,
+	temperature: u32,
+}
+
+impl QuantizedMixture {
+	/// Buckets the given mix's moles by `moles_bucket` and temperature by `temperature_bucket`.
+	pub fn new(mix: &Mixture, moles_bucket: f32, temperature_bucket: f32) -> Self {
+		let mut moles = mix
+			.enumerate()
+			.map(|(_, amt)| (amt / moles_bucket).round().max(0.0) as u32)
+			.collect::<Vec<_>>();
+		// moles past the end are zero anyway, and shouldn't make otherwise-equal mixes unequal
+		while moles.last() == Some(&0) {
+			moles.pop();
+		}
+		Self {
+			moles,
+			temperature: (mix.get_temperature() / temperature_bucket)
+				.round()
+				.max(0.0) as u32,
+		}
+	}
+}
+
+use std::ops::{Add, AddAssign, Mul};
 
 /// Takes a copy of the mix, merges the right hand side, then returns the copy.
 impl Add<&Mixture> for Mixture {
@@ -541,6 +1034,13 @@ impl<'a, 'b> Add<&'a Mixture> for &'b Mixture {
 	}
 }
 
+/// Merges the right hand side into the mix.
+impl AddAssign<&Mixture> for Mixture {
+	fn add_assign(&mut self, rhs: &Mixture) {
+		self.merge(rhs);
+	}
+}
+
 /// Makes a copy of the given mix, multiplied by a scalar.
 impl Mul<f32> for Mixture {
 	type Output = Self;
@@ -563,12 +1063,107 @@ impl<'a> Mul<f32> for &'a Mixture {
 	}
 }
 
+/// Puts a mix together a gas at a time, for tests and anything else making one from scratch.
+/// Only for mixes living on the Rust side; ones byond can see still have to come from the pool.
+#[derive(Clone, Default)]
+pub struct MixtureBuilder {
+	mix: Mixture,
+}
+
+impl MixtureBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn volume(mut self, volume: f32) -> Self {
+		self.mix.volume = volume;
+		self
+	}
+	pub fn temperature(mut self, temperature: f32) -> Self {
+		self.mix.set_temperature(temperature);
+		self
+	}
+	pub fn with_gas(mut self, idx: GasIDX, moles: f32) -> Self {
+		self.mix.set_moles(idx, moles);
+		self
+	}
+	pub fn build(self) -> Mixture {
+		self.mix
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 
+	#[test]
+	fn test_temperature_after_merge() {
+		crate::gas::init_test_gases();
+		let mut cold = Mixture::new();
+		cold.set_moles(0, 100.0);
+		cold.set_temperature(100.0);
+		let mut hot = Mixture::new();
+		hot.set_moles(3, 10.0);
+		hot.set_temperature(1000.0);
+		// 2000 J/K at 100K and 2000 J/K at 1000K
+		let predicted = cold.temperature_after_merge(&hot);
+		assert!((predicted - 550.0).abs() < 0.01);
+		assert_eq!(cold.get_temperature(), 100.0);
+		cold.merge(&hot);
+		assert!((cold.get_temperature() - predicted).abs() < 0.01);
+	}
+	#[test]
+	fn test_composition_hash() {
+		crate::gas::init_test_gases();
+		let mut a = Mixture::new();
+		a.set_moles(0, 20.0);
+		a.set_moles(3, 0.0);
+		let mut b = Mixture::new();
+		b.set_moles(0, 20.0);
+		b.set_temperature(500.0);
+		assert_eq!(a.composition_hash(), b.composition_hash());
+		b.set_moles(0, 20.001);
+		assert_ne!(a.composition_hash(), b.composition_hash());
+	}
+	#[test]
+	fn test_vacuum_sink() {
+		crate::gas::init_test_gases();
+		let mut sink = Mixture::from_vol(CELL_VOLUME);
+		sink.set_moles(1, 50.0);
+		sink.make_vacuum_sink();
+		assert_eq!(sink.total_moles(), 0.0);
+		let mut gas = Mixture::from_vol(CELL_VOLUME);
+		gas.set_moles(0, 100.0);
+		gas.set_temperature(T20C);
+		sink.merge(&gas);
+		assert_eq!(sink.total_moles(), 0.0);
+		assert_eq!(sink.return_pressure(), 0.0);
+	}
+	#[test]
+	fn test_builder() {
+		crate::gas::init_test_gases();
+		let built = MixtureBuilder::new()
+			.volume(CELL_VOLUME)
+			.with_gas(0, 20.0)
+			.with_gas(1, 80.0)
+			.temperature(T20C)
+			.build();
+		let mut manual = Mixture::from_vol(CELL_VOLUME);
+		manual.set_moles(0, 20.0);
+		manual.set_moles(1, 80.0);
+		manual.set_temperature(T20C);
+		assert_eq!(built.volume, manual.volume);
+		assert_eq!(built.get_temperature(), manual.get_temperature());
+		assert_eq!(built.get_moles(0), manual.get_moles(0));
+		assert_eq!(built.get_moles(1), manual.get_moles(1));
+		assert_eq!(built.heat_capacity(), manual.heat_capacity());
+		let mut added = built.clone();
+		added += &manual;
+		assert_eq!(added.total_moles(), 200.0);
+		assert_eq!(added.get_temperature(), T20C);
+	}
 	#[test]
 	fn test_merge() {
+		crate::gas::init_test_gases();
 		let mut into = Mixture::new();
 		into.set_moles(0, 82.0);
 		into.set_moles(1, 22.0);
@@ -600,6 +1195,7 @@ mod tests {
 	#[test]
 	fn test_remove() {
 		// also tests multiply, copy_from_mutable
+		crate::gas::init_test_gases();
 		let mut removed = Mixture::new();
 		removed.set_moles(0, 22.0);
 		removed.set_moles(1, 82.0);
@@ -617,4 +1213,511 @@ mod tests {
 		assert_eq!(removed.get_moles(1), 41.0);
 		assert_eq!(new_two.get_moles(0), 5.5);
 	}
+	#[test]
+	fn test_pressure_cache() {
+		crate::gas::init_test_gases();
+		let pressure_of =
+			|moles: f32, temp: f32, vol: f32| moles * R_IDEAL_GAS_EQUATION * temp / vol;
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(0, 50.0);
+		mix.set_temperature(T20C);
+		assert_eq!(mix.return_pressure(), pressure_of(50.0, T20C, CELL_VOLUME));
+		let mut other = Mixture::from_vol(CELL_VOLUME);
+		other.set_moles(1, 50.0);
+		other.set_temperature(T20C);
+		mix.merge(&other);
+		assert_eq!(mix.return_pressure(), pressure_of(100.0, T20C, CELL_VOLUME));
+		mix.set_temperature(T0C);
+		assert_eq!(mix.return_pressure(), pressure_of(100.0, T0C, CELL_VOLUME));
+		let removed = mix.remove(40.0);
+		assert!((mix.return_pressure() - pressure_of(60.0, T0C, CELL_VOLUME)).abs() < 0.01);
+		assert!((removed.return_pressure() - pressure_of(40.0, T0C, CELL_VOLUME)).abs() < 0.01);
+		mix.clear();
+		assert_eq!(mix.return_pressure(), 0.0);
+	}
+	#[test]
+	fn test_merge_many() {
+		crate::gas::init_test_gases();
+		let mut dst = Mixture::new();
+		dst.set_moles(1, 10.0);
+		dst.set_temperature(T20C);
+		let sources = (0..4)
+			.map(|i| {
+				let mut mix = Mixture::new();
+				mix.set_moles(i, 5.0 * (i + 1) as f32);
+				mix.set_temperature(100.0 * (i + 2) as f32);
+				mix
+			})
+			.collect::<Vec<_>>();
+		let energy_before =
+			dst.thermal_energy() + sources.iter().map(Mixture::thermal_energy).sum::<f32>();
+		let moles_before =
+			dst.total_moles() + sources.iter().map(Mixture::total_moles).sum::<f32>();
+		let mut tot = Mixture::new();
+		for source in &sources {
+			tot.merge(source);
+		}
+		dst.merge(&tot);
+		assert!((dst.total_moles() - moles_before).abs() < 0.001);
+		assert!((dst.thermal_energy() - energy_before).abs() / energy_before < 0.0001);
+	}
+	#[test]
+	fn test_vent_to_space() {
+		crate::gas::init_test_gases();
+		let mut tank = Mixture::from_vol(CELL_VOLUME);
+		tank.set_moles(1, 100.0);
+		tank.set_temperature(T20C);
+		let pressure = tank.return_pressure();
+		// nitrogen's diatomic, so flow chokes below about 0.528 of the tank's pressure
+		let into_vacuum = tank.clone().vent_to_space(1.0, 0.0);
+		assert!((into_vacuum - 47.17).abs() < 0.1);
+		let choked = tank.clone().vent_to_space(1.0, pressure * 0.3);
+		assert!((choked - into_vacuum).abs() < 0.01);
+		let unchoked = tank.clone().vent_to_space(1.0, pressure * 0.9);
+		assert!((unchoked - 10.0).abs() < 0.01);
+		assert_eq!(tank.clone().vent_to_space(1.0, pressure * 2.0), 0.0);
+		let vented = tank.vent_to_space(0.5, 0.0);
+		assert!((tank.total_moles() + vented - 100.0).abs() < 0.01);
+	}
+	#[test]
+	fn test_leak() {
+		crate::gas::init_test_gases();
+		let mut high = Mixture::from_vol(CELL_VOLUME);
+		high.set_moles(1, 100.0);
+		high.set_temperature(T20C);
+		let mut low = Mixture::from_vol(CELL_VOLUME);
+		low.set_temperature(T20C);
+		let leaked = high.leak_into(&mut low, 0.1);
+		assert!((leaked - 5.0).abs() < 0.01);
+		assert!((high.total_moles() + low.total_moles() - 100.0).abs() < 0.01);
+		// a full-conductance leak equalizes, and never overshoots
+		high.leak_into(&mut low, 1.0);
+		assert!((high.return_pressure() - low.return_pressure()).abs() < 0.1);
+		assert!(low.leak_into(&mut high, 0.5) < 0.1);
+		let mut space = Mixture::new();
+		space.mark_immutable();
+		high.leak_into(&mut space, 1.0);
+		assert!(high.total_moles() < 0.01);
+		assert_eq!(space.total_moles(), 0.0);
+	}
+	#[test]
+	fn test_temperature_floor() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(1, 50.0);
+		mix.set_temperature(-100.0);
+		assert_eq!(mix.get_temperature(), TCMB);
+		mix.set_temperature(T20C);
+		mix.set_temperature(0.0);
+		assert_eq!(mix.get_temperature(), TCMB);
+		mix.set_temperature(f32::NAN);
+		assert_eq!(mix.get_temperature(), TCMB);
+		assert_eq!(Mixture::new().get_temperature(), TCMB);
+		mix.set_temperature(T20C);
+		mix.adjust_heat(-1.0e9);
+		assert_eq!(mix.get_temperature(), TCMB);
+		mix.set_thermal_energy(-500.0);
+		assert_eq!(mix.get_temperature(), TCMB);
+		let mut hot = mix.clone();
+		hot.set_temperature(1000.0);
+		mix.temperature_share(&mut hot, 1.0);
+		assert!(mix.get_temperature() >= TCMB && hot.get_temperature() >= TCMB);
+		mix.clear_with_vol(CELL_VOLUME);
+		assert_eq!(mix.get_temperature(), TCMB);
+	}
+	#[test]
+	fn test_refill_toward() {
+		crate::gas::init_test_gases();
+		let mut target = Mixture::from_vol(CELL_VOLUME);
+		target.set_moles(0, 100.0);
+		target.set_temperature(T20C);
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(2, 40.0);
+		mix.set_temperature(T0C);
+		mix.refill_toward(&target, 0.5);
+		assert_eq!(mix.get_moles(0), 50.0);
+		assert_eq!(mix.get_moles(2), 20.0);
+		assert!((mix.get_temperature() - (T0C + T20C) / 2.0).abs() < 0.001);
+		mix.refill_toward(&target, 1.0);
+		assert_eq!(mix.get_moles(0), 100.0);
+		assert_eq!(mix.get_moles(2), 0.0);
+		assert_eq!(mix.get_temperature(), T20C);
+	}
+	#[test]
+	fn test_combustion_ratio() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(3, 10.0);
+		assert_eq!(mix.combustion_ratio(), None);
+		mix.set_moles(0, 20.0);
+		mix.set_moles(1, 80.0);
+		assert_eq!(mix.combustion_ratio(), Some(0.5));
+		mix.set_moles(3, 0.0);
+		assert_eq!(mix.combustion_ratio(), None);
+	}
+	#[test]
+	fn test_moles_to_target_pressure() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(1, MOLES_CELLSTANDARD * 2.0);
+		mix.set_temperature(T20C);
+		let to_remove = mix.moles_to_target_pressure(ONE_ATMOSPHERE);
+		assert!((to_remove - MOLES_CELLSTANDARD).abs() < 0.01);
+		mix.remove(to_remove);
+		assert!((mix.return_pressure() - ONE_ATMOSPHERE).abs() < 0.01);
+		let to_add = -mix.moles_to_target_pressure(ONE_ATMOSPHERE * 3.0);
+		assert!((to_add - MOLES_CELLSTANDARD * 2.0).abs() < 0.01);
+		assert_eq!(Mixture::new().moles_to_target_pressure(ONE_ATMOSPHERE), 0.0);
+	}
+	#[test]
+	fn test_valve_into() {
+		crate::gas::init_test_gases();
+		let mut high = Mixture::from_vol(CELL_VOLUME);
+		high.set_moles(1, 100.0);
+		high.set_temperature(T20C);
+		let mut low = Mixture::from_vol(CELL_VOLUME);
+		low.set_moles(1, 10.0);
+		low.set_temperature(T20C);
+		// backwards through the valve, nothing moves
+		assert_eq!(low.valve_into(&mut high, 1.0), 0.0);
+		assert_eq!(low.get_moles(1), 10.0);
+		assert_eq!(high.get_moles(1), 100.0);
+		let moved = high.valve_into(&mut low, 0.5);
+		assert!(moved > 0.0);
+		assert!((low.get_moles(1) - 10.0 - moved).abs() < 0.01);
+	}
+	#[test]
+	fn test_equalize_into() {
+		crate::gas::init_test_gases();
+		let mut a = Mixture::from_vol(CELL_VOLUME);
+		a.set_moles(0, 60.0);
+		a.set_moles(2, 10.0);
+		a.set_temperature(500.0);
+		let mut b = Mixture::from_vol(CELL_VOLUME * 3.0);
+		b.set_moles(1, 40.0);
+		b.set_temperature(T20C);
+		let energy_before = a.thermal_energy() + b.thermal_energy();
+		a.equalize_into(&mut b);
+		for (idx, amt) in [(0, 60.0), (1, 40.0), (2, 10.0)].iter() {
+			assert!((a.get_moles(*idx) + b.get_moles(*idx) - amt).abs() < 0.001);
+			assert!((b.get_moles(*idx) - 3.0 * a.get_moles(*idx)).abs() < 0.001);
+		}
+		assert!((a.get_temperature() - b.get_temperature()).abs() < 0.001);
+		let energy_after = a.thermal_energy() + b.thermal_energy();
+		assert!((energy_after - energy_before).abs() / energy_before < 0.0001);
+	}
+	#[test]
+	fn test_adiabatic_scale_volume() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(1, 100.0);
+		mix.set_temperature(T20C);
+		mix.adiabatic_scale_volume(CELL_VOLUME / 2.0);
+		assert!(mix.get_temperature() > T20C);
+		assert_eq!(mix.volume, CELL_VOLUME / 2.0);
+		// and back out again gets us where we started
+		mix.adiabatic_scale_volume(CELL_VOLUME);
+		assert!((mix.get_temperature() - T20C).abs() < 0.01);
+		assert_eq!(mix.total_moles(), 100.0);
+		// with a mixed gamma, pressure times volume to the gamma is what stays put
+		mix.set_moles(2, 100.0);
+		let gamma = mix.gamma();
+		let before = mix.return_pressure() * mix.volume.powf(gamma);
+		mix.adiabatic_scale_volume(CELL_VOLUME / 3.0);
+		let after = mix.return_pressure() * mix.volume.powf(gamma);
+		assert!((after / before - 1.0).abs() < 0.001);
+	}
+	#[test]
+	fn test_quantized_mixture() {
+		use std::collections::hash_map::DefaultHasher;
+		use std::hash::{Hash, Hasher};
+		crate::gas::init_test_gases();
+		let hash_of = |q: &QuantizedMixture| {
+			let mut hasher = DefaultHasher::new();
+			q.hash(&mut hasher);
+			hasher.finish()
+		};
+		let mut a = Mixture::new();
+		a.set_moles(0, 20.01);
+		a.set_moles(1, 79.99);
+		a.set_temperature(T20C);
+		let mut b = Mixture::new();
+		b.set_moles(0, 19.99);
+		b.set_moles(1, 80.01);
+		b.set_moles(2, 0.001);
+		b.set_temperature(T20C + 0.1);
+		let (qa, qb) = (
+			QuantizedMixture::new(&a, 0.1, 1.0),
+			QuantizedMixture::new(&b, 0.1, 1.0),
+		);
+		assert!(qa == qb);
+		assert_eq!(hash_of(&qa), hash_of(&qb));
+		b.set_moles(1, 90.0);
+		assert!(qa != QuantizedMixture::new(&b, 0.1, 1.0));
+	}
+	#[test]
+	fn test_remove_by_diffusion() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(0, 50.0);
+		mix.set_moles(3, 50.0);
+		mix.set_temperature(T20C);
+		// gas 0 spreads three times as fast as gas 3
+		let removed = mix.remove_by_diffusion(20.0, &[3.0, 1.0, 1.0, 1.0]);
+		assert!((removed.get_moles(0) - 15.0).abs() < 0.001);
+		assert!((removed.get_moles(3) - 5.0).abs() < 0.001);
+		assert!((mix.total_moles() + removed.total_moles() - 100.0).abs() < 0.001);
+		assert_eq!(removed.get_temperature(), T20C);
+		let mut space = Mixture::new();
+		space.set_moles(0, 50.0);
+		space.set_moles(3, 50.0);
+		space.mark_immutable();
+		let removed = space.remove_by_diffusion(20.0, &[3.0, 1.0, 1.0, 1.0]);
+		assert!((removed.get_moles(0) - 15.0).abs() < 0.001);
+		assert_eq!(space.total_moles(), 100.0);
+	}
+	#[test]
+	fn test_swap_contents() {
+		crate::gas::init_test_gases();
+		let mut a = Mixture::from_vol(100.0);
+		a.set_moles(0, 10.0);
+		a.set_temperature(T0C);
+		let mut b = Mixture::from_vol(200.0);
+		b.set_moles(1, 5.0);
+		b.set_moles(3, 2.0);
+		b.set_temperature(T20C);
+		let (a_before, b_before) = (a.clone(), b.clone());
+		a.swap_contents(&mut b, true);
+		assert_eq!(a.compare(&b_before), 0.0);
+		assert_eq!(b.compare(&a_before), 0.0);
+		assert_eq!(a.get_temperature(), T20C);
+		assert_eq!(b.get_temperature(), T0C);
+		assert_eq!((a.volume, b.volume), (100.0, 200.0));
+		a.swap_contents(&mut b, false);
+		assert_eq!((a.volume, b.volume), (200.0, 100.0));
+		assert_eq!(a.get_moles(0), 10.0);
+	}
+	#[test]
+	fn test_set_thermal_energy() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_temperature(T20C);
+		mix.set_thermal_energy(1_000_000.0);
+		assert_eq!(mix.get_temperature(), T20C);
+		mix.set_moles(0, 50.0);
+		mix.set_thermal_energy(1_000_000.0);
+		assert!((mix.get_temperature() - 1000.0).abs() < 0.01);
+		assert!((mix.thermal_energy() - 1_000_000.0).abs() < 1.0);
+	}
+	#[test]
+	fn test_internal_energy() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(0, 50.0);
+		mix.set_temperature(T20C);
+		assert_eq!(mix.internal_energy(T20C), 0.0);
+		assert!((mix.internal_energy(T0C) - 1000.0 * 20.0).abs() < 1.0);
+		assert!(mix.internal_energy(T20C + 10.0) < 0.0);
+	}
+	#[test]
+	fn test_pressure_at_temperature() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(0, MOLES_CELLSTANDARD);
+		mix.set_temperature(T20C);
+		let doubled = mix.pressure_at_temperature(T20C * 2.0);
+		assert!((doubled - mix.return_pressure() * 2.0).abs() < 0.01);
+		assert_eq!(mix.get_temperature(), T20C);
+		assert_eq!(Mixture::from_vol(0.0).pressure_at_temperature(T20C), 0.0);
+	}
+	#[test]
+	fn test_gases_by_pressure() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(0, 20.0);
+		mix.set_moles(1, 80.0);
+		mix.set_moles(2, 20.0);
+		mix.set_temperature(T20C);
+		let gases = mix.gases_by_pressure();
+		// ties go to the lower id
+		assert_eq!(
+			gases.iter().map(|&(i, _)| i).collect::<Vec<_>>(),
+			vec![1, 0, 2]
+		);
+		assert!((gases[0].1 - mix.partial_pressure(1)).abs() < 0.001);
+		mix.volume = 0.0;
+		assert!(mix.gases_by_pressure().iter().all(|&(_, p)| p == 0.0));
+		assert!(Mixture::new().gases_by_pressure().is_empty());
+	}
+	#[test]
+	fn test_category_moles() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(0, 10.0);
+		mix.set_moles(1, 20.0);
+		mix.set_moles(2, 5.0);
+		mix.set_moles(3, 3.0);
+		assert_eq!(mix.category_moles(1), 30.0);
+		assert_eq!(mix.category_moles(2), 8.0);
+		assert_eq!(mix.category_moles(4), 3.0);
+		assert_eq!(mix.category_moles(1 | 4), 33.0);
+		assert_eq!(mix.category_moles(8), 0.0);
+	}
+	#[test]
+	fn test_molar_mass() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		assert_eq!(mix.molar_mass(), 0.0);
+		mix.set_moles(0, 25.0);
+		mix.set_moles(1, 75.0);
+		// (25 * 32 + 75 * 28) / 100
+		assert!((mix.molar_mass() - 29.0).abs() < 0.001);
+	}
+	#[test]
+	fn test_purity() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		assert_eq!(mix.gas_fraction(0), 0.0);
+		assert!(!mix.is_pure(0, 0.0));
+		mix.set_moles(0, 95.0);
+		mix.set_moles(1, 5.0);
+		assert!((mix.gas_fraction(0) - 0.95).abs() < 0.0001);
+		assert!(mix.is_pure(0, 0.9));
+		assert!(!mix.is_pure(0, 0.99));
+		assert!(!mix.is_pure(3, 0.01));
+	}
+	#[test]
+	fn test_set_specific_heat() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(4, 10.0);
+		assert_eq!(mix.heat_capacity(), 400.0);
+		crate::gas::set_specific_heat(4, 80.0).unwrap();
+		mix.invalidate_heat_capacity();
+		assert_eq!(mix.heat_capacity(), 800.0);
+		assert_eq!(mix.partial_heat_capacity(4), 800.0);
+	}
+	#[test]
+	fn test_is_combustible() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(0, 20.0);
+		mix.set_moles(3, 20.0);
+		mix.set_temperature(T20C);
+		assert!(!mix.is_combustible());
+		mix.set_temperature(FIRE_MINIMUM_TEMPERATURE_TO_EXIST + 10.0);
+		assert!(mix.is_combustible());
+		// no oxygen, no fire, no matter how hot
+		mix.set_moles(0, 0.0);
+		assert!(!mix.is_combustible());
+	}
+	#[test]
+	fn test_audit() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(1, 100.0);
+		mix.set_temperature(T20C);
+		assert_eq!(mix.audit(1000.0), None);
+		assert_eq!(mix.audit(50.0), Some("too many moles"));
+		mix.moles[0] = -1.0;
+		assert_eq!(mix.audit(1000.0), Some("negative moles"));
+		mix.temperature = f32::NAN;
+		assert_eq!(mix.audit(1000.0), Some("non-finite temperature"));
+	}
+	#[test]
+	fn test_reset_if_corrupt() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(1, 100.0);
+		mix.set_temperature(T20C);
+		assert!(!mix.reset_if_corrupt());
+		assert_eq!(mix.total_moles(), 100.0);
+		mix.temperature = f32::NAN;
+		assert!(mix.reset_if_corrupt());
+		assert_eq!(mix.total_moles(), 0.0);
+		assert_eq!(mix.get_temperature(), TCMB);
+		assert_eq!(mix.volume, CELL_VOLUME);
+	}
+	#[test]
+	fn test_hazard_level() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(1, 100.0);
+		assert_eq!(mix.hazard_level(), None);
+		mix.set_moles(2, 30.0);
+		assert_eq!(mix.hazard_level(), Some((2, 3.0)));
+		mix.set_moles(3, 5.0);
+		assert_eq!(mix.hazard_level(), Some((3, 5.0)));
+	}
+	#[test]
+	fn test_blend_toward() {
+		crate::gas::init_test_gases();
+		let mut target = Mixture::new();
+		target.set_moles(0, 10.0);
+		target.set_moles(1, 10.0);
+		target.set_temperature(T20C + 100.0);
+		let mut mix = Mixture::new();
+		mix.set_moles(1, 100.0);
+		mix.set_temperature(T20C);
+		let mut conserved = mix.clone();
+		conserved.blend_toward(&target, 0.5, true);
+		assert!((conserved.get_moles(0) - 25.0).abs() < 0.001);
+		assert!((conserved.get_moles(1) - 75.0).abs() < 0.001);
+		assert!((conserved.get_temperature() - (T20C + 50.0)).abs() < 0.001);
+		mix.blend_toward(&target, 0.5, false);
+		assert!((mix.get_moles(0) - 5.0).abs() < 0.001);
+		assert!((mix.get_moles(1) - 55.0).abs() < 0.001);
+		// already there, so nothing changes
+		let mut converged = target.clone();
+		converged.blend_toward(&target, 0.5, true);
+		assert_eq!(converged.get_moles(0), 10.0);
+		assert_eq!(converged.get_moles(1), 10.0);
+		assert_eq!(converged.get_temperature(), target.get_temperature());
+	}
+	#[test]
+	fn test_gamma() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		assert_eq!(mix.gamma(), DIATOMIC_HEAT_CAPACITY_RATIO);
+		mix.set_moles(1, 50.0);
+		assert!((mix.gamma() - 1.4).abs() < 0.0001);
+		// half and half with a gas of 1.3: Cv goes to (2.5 + 3.333) / 2 R
+		mix.set_moles(2, 50.0);
+		assert!((mix.gamma() - (1.0 + 1.0 / 2.916_667)).abs() < 0.0001);
+	}
+	#[test]
+	fn test_suppressed_reactions() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(0, 20.0);
+		mix.set_moles(3, 20.0);
+		mix.set_temperature(FIRE_MINIMUM_TEMPERATURE_TO_EXIST + 1000.0);
+		assert!(mix.can_react());
+		mix.set_reactions_suppressed(true);
+		assert!(!mix.can_react());
+		assert!(mix.all_reactable().is_empty());
+		mix.set_reactions_suppressed(false);
+		assert!(!mix.all_reactable().is_empty());
+	}
+	#[test]
+	fn test_save_string() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(0, 21.5);
+		mix.set_moles(3, 0.125);
+		mix.set_temperature(T20C);
+		let saved = mix.to_save_string().unwrap();
+		let (loaded, dropped) = Mixture::from_save_string(&saved).unwrap();
+		assert!(dropped.is_empty());
+		assert_eq!(loaded.get_moles(0), 21.5);
+		assert_eq!(loaded.get_moles(3), 0.125);
+		assert_eq!(loaded.get_temperature(), T20C);
+		assert_eq!(loaded.volume, CELL_VOLUME);
+		// a gas that's since been removed gets dropped, and the rest still lands in the right place
+		let (loaded, dropped) =
+			Mixture::from_save_string(&format!("{};unobtainium=5", saved)).unwrap();
+		assert_eq!(dropped, vec!["unobtainium".to_string()]);
+		assert_eq!(loaded.get_moles(0), 21.5);
+		assert!(Mixture::from_save_string("hot;2500").is_err());
+	}
 }
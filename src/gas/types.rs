@@ -6,7 +6,7 @@ use std::cell::RefCell;
 
 use crate::reaction::Reaction;
 
-use super::GasIDX;
+use super::{constants::DIATOMIC_HEAT_CAPACITY_RATIO, GasIDX};
 
 use dashmap::DashMap;
 
@@ -106,12 +106,32 @@ pub struct GasType {
 	/// Not used in auxmos, there for completeness. Only flag on Citadel is GAS_DANGEROUS.
 	/// Byond: `flags`, a number (bitflags).
 	pub flags: u32,
+	/// Which groups the gas belongs to, like breathable or toxic, for air alarms and such. Defaults to none.
+	/// Duplicated in the GAS_CATEGORIES vector for speed.
+	/// Byond: `categories`, a number (bitflags).
+	pub categories: u32,
 	/// The specific heat of the gas. Duplicated in the GAS_SPECIFIC_HEATS vector for speed.
 	/// Byond: `specific_heat`, a number.
 	pub specific_heat: f32,
+	/// Grams per mole, for buoyancy and the like. Defaults to 0.
+	/// Duplicated in the GAS_MOLAR_MASSES vector for speed.
+	/// Byond: `molar_mass`, a number.
+	pub molar_mass: f32,
+	/// The ratio of the gas's constant-pressure to constant-volume specific heats. Defaults to a diatomic gas's 1.4,
+	/// and has to be above 1. Duplicated in the GAS_HEAT_CAPACITY_RATIOS vector for speed.
+	/// Byond: `heat_capacity_ratio`, a number.
+	pub heat_capacity_ratio: f32,
+	/// How quickly the gas spreads relative to others; only used by diffusion-weighted equalization. Defaults to 1.
+	/// Duplicated in the GAS_DIFFUSION_COEFFICIENTS vector for speed.
+	/// Byond: `diffusion_coefficient`, a number.
+	pub diffusion_coefficient: f32,
 	/// Gas's fusion power. Used in fusion hooking, so this can be removed and ignored if you don't have fusion.
 	/// Byond: `fusion_power`, a number.
 	pub fusion_power: f32,
+	/// The moles at which the gas starts being a hazard, for air alarms. If None, the gas is never dangerous.
+	/// Duplicated in the GAS_DANGER_THRESHOLDS vector for speed.
+	/// Byond: `danger_threshold`, a number.
+	pub danger_threshold: Option<f32>,
 	/// The moles at which the gas's overlay or other appearance shows up. If None, gas is never visible.
 	/// Byond: `moles_visible`, a number.
 	pub moles_visible: Option<f32>,
@@ -141,6 +161,9 @@ impl GasType {
 					std::column!()
 				)
 			})? as u32,
+			categories: gas
+				.get_number(byond_string!("categories"))
+				.map_or(0, |n| n as u32),
 			specific_heat: gas
 				.get_number(byond_string!("specific_heat"))
 				.map_err(|_| {
@@ -151,6 +174,15 @@ impl GasType {
 						std::column!()
 					)
 				})?,
+			molar_mass: gas.get_number(byond_string!("molar_mass")).unwrap_or(0.0),
+			heat_capacity_ratio: gas
+				.get_number(byond_string!("heat_capacity_ratio"))
+				.ok()
+				.filter(|&ratio| ratio > 1.0)
+				.unwrap_or(DIATOMIC_HEAT_CAPACITY_RATIO),
+			diffusion_coefficient: gas
+				.get_number(byond_string!("diffusion_coefficient"))
+				.unwrap_or(1.0),
 			fusion_power: gas.get_number(byond_string!("fusion_power")).map_err(|_| {
 				runtime!(
 					"Attempt to interpret non-number value as number {} {}:{}",
@@ -159,6 +191,10 @@ impl GasType {
 					std::column!()
 				)
 			})?,
+			danger_threshold: gas
+				.get_number(byond_string!("danger_threshold"))
+				.ok()
+				.filter(|&threshold| threshold > 0.0),
 			moles_visible: gas.get_number(byond_string!("moles_visible")).ok(),
 			fire_info: {
 				if let Ok(temperature) = gas.get_number(byond_string!("oxidation_temperature")) {
@@ -201,6 +237,16 @@ static GAS_INFO_BY_IDX: RwLock<Option<Vec<GasType>>> = const_rwlock(None);
 
 static GAS_SPECIFIC_HEATS: RwLock<Option<Vec<f32>>> = const_rwlock(None);
 
+static GAS_MOLAR_MASSES: RwLock<Option<Vec<f32>>> = const_rwlock(None);
+
+static GAS_HEAT_CAPACITY_RATIOS: RwLock<Option<Vec<f32>>> = const_rwlock(None);
+
+static GAS_DIFFUSION_COEFFICIENTS: RwLock<Option<Vec<f32>>> = const_rwlock(None);
+
+static GAS_CATEGORIES: RwLock<Option<Vec<u32>>> = const_rwlock(None);
+
+static GAS_DANGER_THRESHOLDS: RwLock<Option<Vec<Option<f32>>>> = const_rwlock(None);
+
 #[init(partial)]
 fn _create_gas_info_structs() -> Result<(), String> {
 	unsafe {
@@ -208,6 +254,11 @@ fn _create_gas_info_structs() -> Result<(), String> {
 	};
 	*GAS_INFO_BY_IDX.write() = Some(Vec::new());
 	*GAS_SPECIFIC_HEATS.write() = Some(Vec::new());
+	*GAS_MOLAR_MASSES.write() = Some(Vec::new());
+	*GAS_HEAT_CAPACITY_RATIOS.write() = Some(Vec::new());
+	*GAS_DIFFUSION_COEFFICIENTS.write() = Some(Vec::new());
+	*GAS_CATEGORIES.write() = Some(Vec::new());
+	*GAS_DANGER_THRESHOLDS.write() = Some(Vec::new());
 	Ok(())
 }
 
@@ -218,29 +269,130 @@ fn _destroy_gas_info_structs() {
 	};
 	*GAS_INFO_BY_IDX.write() = None;
 	*GAS_SPECIFIC_HEATS.write() = None;
+	*GAS_MOLAR_MASSES.write() = None;
+	*GAS_HEAT_CAPACITY_RATIOS.write() = None;
+	*GAS_DIFFUSION_COEFFICIENTS.write() = None;
+	*GAS_CATEGORIES.write() = None;
+	*GAS_DANGER_THRESHOLDS.write() = None;
 	TOTAL_NUM_GASES.store(0, Ordering::Release);
 	CACHED_GAS_IDS.with(|gas_ids| {
 		gas_ids.borrow_mut().clear();
 	});
 }
 
-#[hook("/proc/_auxtools_register_gas")]
-fn _hook_register_gas(gas: Value) {
-	let gas_id = gas.get_string(byond_string!("id")).unwrap();
-	let gas_cache = GasType::new(gas, TOTAL_NUM_GASES.load(Ordering::Relaxed))?;
+fn register_gas_type(gas_cache: GasType) {
 	unsafe { GAS_INFO_BY_STRING.as_ref() }
 		.unwrap()
-		.insert(gas_id.into_boxed_str(), gas_cache.clone());
+		.insert(gas_cache.id.clone(), gas_cache.clone());
 	GAS_SPECIFIC_HEATS
 		.write()
 		.as_mut()
 		.unwrap()
 		.push(gas_cache.specific_heat);
+	GAS_MOLAR_MASSES
+		.write()
+		.as_mut()
+		.unwrap()
+		.push(gas_cache.molar_mass);
+	GAS_HEAT_CAPACITY_RATIOS
+		.write()
+		.as_mut()
+		.unwrap()
+		.push(gas_cache.heat_capacity_ratio);
+	GAS_DIFFUSION_COEFFICIENTS
+		.write()
+		.as_mut()
+		.unwrap()
+		.push(gas_cache.diffusion_coefficient);
+	GAS_CATEGORIES
+		.write()
+		.as_mut()
+		.unwrap()
+		.push(gas_cache.categories);
+	GAS_DANGER_THRESHOLDS
+		.write()
+		.as_mut()
+		.unwrap()
+		.push(gas_cache.danger_threshold);
 	GAS_INFO_BY_IDX.write().as_mut().unwrap().push(gas_cache);
 	TOTAL_NUM_GASES.fetch_add(1, Ordering::Release); // this is the only thing that stores it other than shutdown
+}
+
+#[hook("/proc/_auxtools_register_gas")]
+fn _hook_register_gas(gas: Value) {
+	let gas_cache = GasType::new(gas, TOTAL_NUM_GASES.load(Ordering::Relaxed))?;
+	register_gas_type(gas_cache);
 	Ok(Value::null())
 }
 
+/// Registers a small Citadel-like gas set (o2, n2, co2, plasma, water vapor, in that order) and the test reactions,
+/// since unit tests have no byond to get them from. Safe to call from every test.
+#[cfg(test)]
+pub(crate) fn init_test_gases() {
+	static INIT: std::sync::Once = std::sync::Once::new();
+	INIT.call_once(|| {
+		_create_gas_info_structs().unwrap();
+		let test_gas = |id: &str, specific_heat: f32, fire_info: FireInfo| GasType {
+			idx: TOTAL_NUM_GASES.load(Ordering::Relaxed),
+			id: id.into(),
+			name: id.into(),
+			flags: 0,
+			categories: 0,
+			specific_heat,
+			molar_mass: 0.0,
+			heat_capacity_ratio: DIATOMIC_HEAT_CAPACITY_RATIO,
+			diffusion_coefficient: 1.0,
+			fusion_power: 0.0,
+			danger_threshold: None,
+			moles_visible: None,
+			fire_energy_released: 0.0,
+			fire_info,
+			fire_products: None,
+		};
+		// categories: 1 is breathable, 2 is toxic, 4 is fuel
+		register_gas_type(GasType {
+			categories: 1,
+			molar_mass: 32.0,
+			..test_gas(
+				super::constants::GAS_O2,
+				20.0,
+				FireInfo::Oxidation(OxidationInfo {
+					temperature: super::constants::FIRE_MINIMUM_TEMPERATURE_TO_EXIST,
+					power: 1.0,
+				}),
+			)
+		});
+		register_gas_type(GasType {
+			categories: 1,
+			molar_mass: 28.0,
+			..test_gas(super::constants::GAS_N2, 20.0, FireInfo::None)
+		});
+		register_gas_type(GasType {
+			categories: 2,
+			heat_capacity_ratio: 1.3,
+			danger_threshold: Some(10.0),
+			..test_gas(super::constants::GAS_CO2, 30.0, FireInfo::None)
+		});
+		register_gas_type(GasType {
+			categories: 2 | 4,
+			danger_threshold: Some(1.0),
+			..test_gas(
+				super::constants::GAS_PLASMA,
+				200.0,
+				FireInfo::Fuel(FuelInfo {
+					temperature: super::constants::PLASMA_MINIMUM_BURN_TEMPERATURE,
+					burn_rate: 3.0,
+				}),
+			)
+		});
+		// only test_set_specific_heat touches this one, so it can change it freely
+		register_gas_type(test_gas(super::constants::GAS_H2O, 40.0, FireInfo::None));
+		unsafe {
+			REACTION_INFO = Some(crate::reaction::test_reactions());
+		};
+	});
+}
+
 #[hook("/proc/auxtools_atmos_init")]
 fn _hook_init() {
 	let data = Value::globals()
@@ -278,6 +430,7 @@ fn _update_reactions() {
 	unsafe {
 		REACTION_INFO = Some(get_reaction_info());
 	};
+	crate::reaction::clear_reaction_stats();
 	Ok(Value::from(true))
 }
 
@@ -293,6 +446,115 @@ pub fn with_specific_heats<T>(f: impl FnOnce(&[f32]) -> T) -> T {
 	f(GAS_SPECIFIC_HEATS.read().as_ref().unwrap().as_slice())
 }
 
+pub fn with_molar_masses<T>(f: impl FnOnce(&[f32]) -> T) -> T {
+	f(GAS_MOLAR_MASSES.read().as_ref().unwrap().as_slice())
+}
+
+pub fn with_heat_capacity_ratios<T>(f: impl FnOnce(&[f32]) -> T) -> T {
+	f(GAS_HEAT_CAPACITY_RATIOS.read().as_ref().unwrap().as_slice())
+}
+
+pub fn with_diffusion_coefficients<T>(f: impl FnOnce(&[f32]) -> T) -> T {
+	f(GAS_DIFFUSION_COEFFICIENTS
+		.read()
+		.as_ref()
+		.unwrap()
+		.as_slice())
+}
+
+pub fn with_gas_categories<T>(f: impl FnOnce(&[u32]) -> T) -> T {
+	f(GAS_CATEGORIES.read().as_ref().unwrap().as_slice())
+}
+
+pub fn with_danger_thresholds<T>(f: impl FnOnce(&[Option<f32>]) -> T) -> T {
+	f(GAS_DANGER_THRESHOLDS.read().as_ref().unwrap().as_slice())
+}
+
+/// Changes which categories a gas is in after it's been registered.
+pub fn set_gas_categories(idx: GasIDX, categories: u32) -> Result<(), Runtime> {
+	let id = {
+		let mut lock = GAS_INFO_BY_IDX.write();
+		let gas = lock
+			.as_mut()
+			.ok_or_else(|| runtime!("Gases not loaded yet! Uh oh!"))?
+			.get_mut(idx)
+			.ok_or_else(|| runtime!("Invalid gas index: {}", idx))?;
+		gas.categories = categories;
+		gas.id.clone()
+	};
+	if let Some(mut gas) = unsafe { GAS_INFO_BY_STRING.as_ref() }.and_then(|map| map.get_mut(&id)) {
+		gas.categories = categories;
+	}
+	if let Some(cats) = GAS_CATEGORIES
+		.write()
+		.as_mut()
+		.and_then(|cats| cats.get_mut(idx))
+	{
+		*cats = categories;
+	}
+	Ok(())
+}
+
+#[hook("/proc/set_gas_categories")]
+fn _hook_set_gas_categories(gas_id: Value, categories_val: Value) {
+	let categories = categories_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})? as u32;
+	set_gas_categories(gas_idx_from_value(gas_id)?, categories)?;
+	Ok(Value::null())
+}
+
+/// Changes a gas's specific heat after it's been registered. Mixtures cache their heat capacities,
+/// so callers need to clear those afterward, as `set_gas_specific_heat` does.
+pub fn set_specific_heat(idx: GasIDX, specific_heat: f32) -> Result<(), Runtime> {
+	let id = {
+		let mut lock = GAS_INFO_BY_IDX.write();
+		let gas = lock
+			.as_mut()
+			.ok_or_else(|| runtime!("Gases not loaded yet! Uh oh!"))?
+			.get_mut(idx)
+			.ok_or_else(|| runtime!("Invalid gas index: {}", idx))?;
+		gas.specific_heat = specific_heat;
+		gas.id.clone()
+	};
+	if let Some(mut gas) = unsafe { GAS_INFO_BY_STRING.as_ref() }.and_then(|map| map.get_mut(&id)) {
+		gas.specific_heat = specific_heat;
+	}
+	if let Some(heat) = GAS_SPECIFIC_HEATS
+		.write()
+		.as_mut()
+		.and_then(|heats| heats.get_mut(idx))
+	{
+		*heat = specific_heat;
+	}
+	Ok(())
+}
+
+#[hook("/proc/set_gas_specific_heat")]
+fn _hook_set_specific_heat(gas_id: Value, heat_val: Value) {
+	let specific_heat = heat_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	if !specific_heat.is_finite() || specific_heat < 0.0 {
+		return Err(runtime!(
+			"Attempted to set a specific heat to a negative number, NaN or infinity."
+		));
+	}
+	set_specific_heat(gas_idx_from_value(gas_id)?, specific_heat)?;
+	super::GasArena::invalidate_heat_capacities();
+	Ok(Value::null())
+}
+
 #[cfg(feature = "reaction_hooks")]
 pub fn gas_fusion_power(idx: &GasIDX) -> f32 {
 	GAS_INFO_BY_IDX
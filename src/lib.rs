@@ -1,5 +1,6 @@
 pub mod gas;
 
+#[macro_use]
 pub mod turfs;
 
 pub mod reaction;
@@ -10,10 +11,42 @@ use auxcleanup::*;
 
 use gas::*;
 
-use reaction::react_by_id;
+use reaction::{
+	dispatch_side_effect, ignition_gap, react_by_id, record_reaction, run_reactions,
+	simultaneous_reactions, IgnitionGap, ReactionIdentifier,
+};
 
 use gas::constants::*;
 
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// Bits of the f32 that pressures are multiplied by on the way out. Starts at 1.0.
+static PRESSURE_SCALE: AtomicU32 = AtomicU32::new(0x3F80_0000);
+
+pub(crate) fn pressure_scale() -> f32 {
+	f32::from_bits(PRESSURE_SCALE.load(Ordering::Relaxed))
+}
+
+/// Sets what pressures reported to DM get multiplied by, e.g. `1/ONE_ATMOSPHERE` for atmospheres.
+/// Everything inside is still in kilopascals; this only changes what the procs take and return.
+/// Reaction pressure requirements aren't proc arguments, so they stay in kilopascals, going in and coming back out.
+#[hook("/proc/set_pressure_scale")]
+fn _set_pressure_scale_hook(scale_val: Value) {
+	let scale = scale_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	if !scale.is_normal() || scale < 0.0 {
+		return Err(runtime!("Invalid pressure scale: {}", scale));
+	}
+	PRESSURE_SCALE.store(scale.to_bits(), Ordering::Relaxed);
+	Ok(Value::null())
+}
+
 #[hook("/proc/process_atmos_callbacks")]
 fn _atmos_callback_handle() {
 	auxcallback::callback_processing_hook(args)
@@ -37,6 +70,54 @@ fn _unregister_gasmixture_hook(v: u32) {
 	gas::GasArena::unregister_mix(v);
 }
 
+/// Unregisters every gas mixture in the list in one go, for tearing down whole areas at once.
+/// Mixes that are already unregistered are skipped. Returns how many were unregistered.
+#[hook("/proc/unregister_gasmixes")]
+fn _unregister_gasmixes_hook(list_val: Value) {
+	let value_list = list_val.as_list().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-list value as list {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	let mixes = (1..=value_list.len())
+		.filter_map(|i| value_list.get(i).ok())
+		.collect::<Vec<_>>();
+	Ok(Value::from(gas::GasArena::unregister_many(&mixes) as f32))
+}
+
+/// Returns null if the mix's pointer is fine, or what's wrong with it otherwise.
+/// If told to repair with an optional second argument, gives the mix a fresh, empty gas mixture when the pointer's bad.
+#[hook("/proc/validate_gasmix_pointer")]
+fn _validate_pointer_hook(mix: Value) {
+	let repair = args
+		.get(1)
+		.map_or(false, |v| v.as_number().unwrap_or_default() != 0.0);
+	match gas::GasArena::check_mix_pointer(mix) {
+		Some(problem) => {
+			if repair {
+				gas::GasArena::register_mix(mix)?;
+			}
+			Value::from_string(problem.as_str())
+		}
+		None => Ok(Value::null()),
+	}
+}
+
+/// The pool slot behind the mix, for matching it up with audit_all_mixtures and such; -1 if it's not registered.
+#[hook("/proc/gasmix_pool_index")]
+fn _gasmix_pool_index_hook(mix: Value) {
+	if gas::GasArena::check_mix_pointer(mix).is_some() {
+		return Ok(Value::from(-1.0));
+	}
+	Ok(Value::from(
+		mix.get_number(byond_string!("_extools_pointer_gasmixture"))?
+			.to_bits() as f32,
+	))
+}
+
 #[hook("/datum/gas_mixture/proc/heat_capacity")]
 fn _heat_cap_hook() {
 	with_mix(src, |mix| Ok(Value::from(mix.heat_capacity())))
@@ -63,7 +144,130 @@ fn _total_moles_hook() {
 
 #[hook("/datum/gas_mixture/proc/return_pressure")]
 fn _return_pressure_hook() {
-	with_mix(src, |mix| Ok(Value::from(mix.return_pressure())))
+	with_mix(src, |mix| {
+		Ok(Value::from(mix.return_pressure() * pressure_scale()))
+	})
+}
+
+#[hook("/proc/gasmix_purity")]
+fn _gasmix_purity_hook(mix_val: Value, gas_id: Value) {
+	let idx = gas_idx_from_value(gas_id)?;
+	with_mix(mix_val, |mix| Ok(Value::from(mix.gas_fraction(idx))))
+}
+
+/// Fuel to oxidizer ratio of the mix's most plentiful fuel and oxidizer, or null if it's missing either.
+#[hook("/proc/combustion_ratio")]
+fn _combustion_ratio_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| {
+		Ok(mix.combustion_ratio().map_or_else(Value::null, Value::from))
+	})
+}
+
+/// What the mix is missing before it'll burn: list("gas" = id, "moles" = moles short), list("fire_reagents" = amount short),
+/// list("pressure" = pressure short, negative if over) or list("temperature" = kelvins short).
+/// Null if it can already burn or never would.
+#[hook("/proc/ignition_gap")]
+fn _ignition_gap_hook(mix_val: Value) {
+	let gap = with_mix(mix_val, |mix| Ok(ignition_gap(mix)))?;
+	let ret = List::new();
+	match gap {
+		Some(IgnitionGap::Moles(idx, moles)) => {
+			ret.set(
+				byond_string!("gas"),
+				Value::from_string(&*gas_idx_to_id(idx)?)?,
+			)?;
+			ret.set(byond_string!("moles"), Value::from(moles))?;
+		}
+		Some(IgnitionGap::FireReagents(amount)) => {
+			ret.set(byond_string!("fire_reagents"), Value::from(amount))?;
+		}
+		Some(IgnitionGap::Pressure(pressure)) => {
+			ret.set(
+				byond_string!("pressure"),
+				Value::from(pressure * pressure_scale()),
+			)?;
+		}
+		Some(IgnitionGap::Temperature(kelvins)) => {
+			ret.set(byond_string!("temperature"), Value::from(kelvins))?;
+		}
+		None => return Ok(Value::null()),
+	}
+	Ok(Value::from(ret))
+}
+
+/// How many moles to pull out of the mix to get it to the target pressure, or negative for how many to put in.
+#[hook("/proc/moles_to_target_pressure")]
+fn _moles_to_target_pressure_hook(mix_val: Value, target_val: Value) {
+	let target = target_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})? / pressure_scale();
+	with_mix(mix_val, |mix| {
+		Ok(Value::from(mix.moles_to_target_pressure(target)))
+	})
+}
+
+/// How dangerous the mix is, for air alarms: a list of the worst gas's moles over its danger threshold, then that gas.
+/// Safe air gets 0 and null.
+#[hook("/proc/gasmix_hazard_level")]
+fn _gasmix_hazard_level_hook(mix_val: Value) {
+	let hazard = with_mix(mix_val, |mix| Ok(mix.hazard_level()))?;
+	let ret = List::new();
+	match hazard {
+		Some((idx, ratio)) => {
+			ret.append(Value::from(ratio));
+			ret.append(Value::from_string(&*gas_idx_to_id(idx)?)?);
+		}
+		None => {
+			ret.append(Value::from(0.0));
+			ret.append(Value::null());
+		}
+	}
+	Ok(Value::from(ret))
+}
+
+#[hook("/proc/gasmix_gamma")]
+fn _gasmix_gamma_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| Ok(Value::from(mix.gamma())))
+}
+
+#[hook("/proc/gasmix_molar_mass")]
+fn _gasmix_molar_mass_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| Ok(Value::from(mix.molar_mass())))
+}
+
+#[hook("/proc/category_moles")]
+fn _category_moles_hook(mix_val: Value, category_val: Value) {
+	let category = category_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})? as u32;
+	with_mix(mix_val, |mix| Ok(Value::from(mix.category_moles(category))))
+}
+
+#[hook("/proc/pressure_at_temperature")]
+fn _pressure_at_temperature_hook(mix_val: Value, temp_val: Value) {
+	let temp = temp_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	with_mix(mix_val, |mix| {
+		Ok(Value::from(
+			mix.pressure_at_temperature(temp) * pressure_scale(),
+		))
+	})
 }
 
 #[hook("/datum/gas_mixture/proc/return_temperature")]
@@ -76,11 +280,87 @@ fn _return_volume_hook() {
 	with_mix(src, |mix| Ok(Value::from(mix.volume)))
 }
 
+// Same as return_volume, for callers holding a mix they'd rather not call procs on.
+#[hook("/proc/gasmix_volume")]
+fn _gasmix_volume_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| Ok(Value::from(mix.volume)))
+}
+
+#[hook("/proc/gasmix_density")]
+fn _gasmix_density_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| Ok(Value::from(mix.density())))
+}
+
+#[hook("/proc/set_gasmix_density")]
+fn _set_gasmix_density_hook(mix_val: Value, density_val: Value) {
+	let v = density_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	if v.is_finite() && v >= 0.0 {
+		with_mix_mut(mix_val, |mix| {
+			mix.set_density(v);
+			Ok(Value::null())
+		})
+	} else {
+		Err(runtime!(
+			"Attempted to set density to a negative number, NaN or infinity."
+		))
+	}
+}
+
 #[hook("/datum/gas_mixture/proc/thermal_energy")]
 fn _thermal_energy_hook() {
 	with_mix(src, |mix| Ok(Value::from(mix.thermal_energy())))
 }
 
+#[hook("/proc/thermal_energy_of")]
+fn _thermal_energy_of_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| {
+		if mix.total_moles() > 0.0 {
+			Ok(Value::from(mix.thermal_energy()))
+		} else {
+			Ok(Value::from(0.0))
+		}
+	})
+}
+
+/// Thermal energy relative to a reference temperature, for heat exchangers working off an ambient temperature.
+#[hook("/proc/internal_energy")]
+fn _internal_energy_hook(mix_val: Value, reference_val: Value) {
+	let reference_temperature = reference_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	with_mix(mix_val, |mix| {
+		Ok(Value::from(mix.internal_energy(reference_temperature)))
+	})
+}
+
+#[hook("/datum/gas_mixture/proc/set_thermal_energy")]
+fn _set_thermal_energy_hook(energy_val: Value) {
+	let energy = energy_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	with_mix_mut(src, |mix| {
+		mix.set_thermal_energy(energy);
+		Ok(Value::null())
+	})
+}
+
 #[hook("/datum/gas_mixture/proc/merge")]
 fn _merge_hook() {
 	if args.is_empty() {
@@ -117,6 +397,30 @@ fn _remove_hook() {
 	}
 }
 
+/// What'd have to be added to a to make it match target, as a new mix at the target's temperature and volume.
+/// If given a list as a third argument, what'd have to be taken out goes in it too, as gas id to moles.
+#[hook("/proc/gasmix_difference")]
+fn _difference_hook(a: Value, target: Value) {
+	let (mut add_mix, mut remove_mix) = (Mixture::new(), Mixture::new());
+	let volume = with_mixes(a, target, |a_mix, target_mix| {
+		a_mix.difference_into(target_mix, &mut add_mix, &mut remove_mix);
+		Ok(target_mix.volume)
+	})?;
+	let ret = gas::new_gas_mixture(volume)?;
+	with_mix_mut(&ret, |mix| {
+		mix.copy_from_mutable(&add_mix);
+		Ok(())
+	})?;
+	if let Some(removals) = args.get(2).and_then(|v| v.as_list().ok()) {
+		for (idx, amount) in remove_mix.enumerate() {
+			if amount > 0.0 {
+				removals.set(Value::from_string(&*gas_idx_to_id(idx)?)?, amount)?;
+			}
+		}
+	}
+	Ok(ret)
+}
+
 #[hook("/datum/gas_mixture/proc/copy_from")]
 fn _copy_from_hook() {
 	if args.is_empty() {
@@ -129,6 +433,45 @@ fn _copy_from_hook() {
 	}
 }
 
+#[hook("/datum/gas_mixture/proc/to_save_string")]
+fn _to_save_string_hook() {
+	with_mix(src, |mix| Value::from_string(mix.to_save_string()?))
+}
+
+/// Loads a mix saved by to_save_string into this one. Gases that have since been removed are dropped,
+/// with a stack trace listing them.
+#[hook("/datum/gas_mixture/proc/load_save_string")]
+fn _load_save_string_hook(saved: Value) {
+	let (loaded, dropped) = Mixture::from_save_string(&saved.as_string()?)?;
+	with_mix_mut(src, |mix| {
+		mix.copy_from_mutable(&loaded);
+		mix.volume = loaded.volume;
+		Ok(())
+	})?;
+	if !dropped.is_empty() {
+		if let Some(stack_trace) = Proc::find(byond_string!("/proc/stack_trace")) {
+			stack_trace.call(&[&Value::from_string(&format!(
+				"Dropped gases that no longer exist while loading a saved mix: {}",
+				dropped.join(", ")
+			))?])?;
+		}
+	}
+	Ok(Value::null())
+}
+
+/// A new mix with everything the given one has: gases, temperature and volume.
+#[hook("/proc/clone_gasmix")]
+fn _clone_hook(mix_val: Value) {
+	let original = with_mix(mix_val, |mix| Ok(mix.clone()))?;
+	let clone = gas::new_gas_mixture(original.volume)?;
+	with_mix_mut(&clone, |mix| {
+		mix.copy_from_mutable(&original);
+		mix.volume = original.volume;
+		Ok(())
+	})?;
+	Ok(clone)
+}
+
 #[hook("/datum/gas_mixture/proc/temperature_share")]
 fn _temperature_share_hook() {
 	let arg_num = args.len();
@@ -164,6 +507,65 @@ fn _get_gases_hook() {
 	})
 }
 
+#[hook("/proc/visible_gases")]
+fn _get_visible_gases_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| {
+		let gases_list: List = List::new();
+		for idx in mix.visible_gases() {
+			gases_list.append(Value::from_string(&*gas_idx_to_id(idx)?)?);
+		}
+		Ok(Value::from(gases_list))
+	})
+}
+
+/// Every gas in the mix past trace amounts, as a list of list(gas id, partial pressure), highest pressure first.
+/// Ties go by gas id; an empty mix gets an empty list.
+#[hook("/proc/gases_by_pressure")]
+fn _gases_by_pressure_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| {
+		let gases_list: List = List::new();
+		for (idx, pressure) in mix.gases_by_pressure() {
+			let pair = List::new();
+			pair.append(Value::from_string(&*gas_idx_to_id(idx)?)?);
+			pair.append(Value::from(pressure * pressure_scale()));
+			gases_list.append(Value::from(pair));
+		}
+		Ok(Value::from(gases_list))
+	})
+}
+
+/// The gas there's the most of across every mix in use, or null if there's no gas anywhere.
+#[hook("/proc/most_common_station_gas")]
+fn _most_common_station_gas_hook() {
+	let most_common = GasArena::live_gas_totals()
+		.into_iter()
+		.enumerate()
+		.filter(|&(_, amt)| amt > 0.0)
+		.max_by(|&(_, a), &(_, b)| float_ord::FloatOrd(a).cmp(&float_ord::FloatOrd(b)));
+	match most_common {
+		Some((idx, _)) => Value::from_string(&*gas_idx_to_id(idx)?),
+		None => Ok(Value::null()),
+	}
+}
+
+/// Every gas in the mix with any moles at all, as an associative list of gas id to moles.
+#[hook("/proc/gas_composition")]
+fn _gas_composition_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| {
+		let gases_list: List = List::new();
+		mix.for_each_gas(|idx, amt| {
+			if amt > 0.0 {
+				gases_list.set(
+					&Value::from_string(&*gas_idx_to_id(idx)?)?,
+					Value::from(amt),
+				)?;
+			}
+			Ok(())
+		})?;
+		Ok(Value::from(gases_list))
+	})
+}
+
 #[hook("/datum/gas_mixture/proc/set_temperature")]
 fn _set_temperature_hook() {
 	let v = args
@@ -180,7 +582,7 @@ fn _set_temperature_hook() {
 		})?;
 	if v.is_finite() {
 		with_mix_mut(src, |mix| {
-			mix.set_temperature(v.max(2.7));
+			mix.set_temperature(v.max(TCMB));
 			Ok(Value::null())
 		})
 	} else {
@@ -222,6 +624,21 @@ fn _set_volume_hook() {
 	}
 }
 
+#[hook("/proc/adiabatic_resize")]
+fn _adiabatic_resize_hook(mix_val: Value, new_volume: Value) {
+	with_mix_mut(mix_val, |mix| {
+		mix.adiabatic_scale_volume(new_volume.as_number().map_err(|_| {
+			runtime!(
+				"Attempt to interpret non-number value as number {} {}:{}",
+				std::file!(),
+				std::line!(),
+				std::column!()
+			)
+		})?);
+		Ok(Value::null())
+	})
+}
+
 #[hook("/datum/gas_mixture/proc/get_moles")]
 fn _get_moles_hook() {
 	if args.is_empty() {
@@ -295,6 +712,50 @@ fn _mark_immutable_hook() {
 	with_mix_mut(src, |mix| {
 		mix.mark_immutable();
 		Ok(Value::null())
+	})?;
+	turfs::bump_topology_generation();
+	Ok(Value::null())
+}
+
+/// Turns the mix into somewhere machinery can dump gas: always empty, and anything merged in is discarded.
+#[hook("/proc/make_vacuum_sink")]
+fn _make_vacuum_sink_hook(mix_val: Value) {
+	with_mix_mut(mix_val, |mix| {
+		mix.make_vacuum_sink();
+		Ok(Value::null())
+	})?;
+	turfs::bump_topology_generation();
+	Ok(Value::null())
+}
+
+#[hook("/proc/gasmix_is_immutable")]
+fn _is_immutable_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| Ok(Value::from(mix.is_immutable())))
+}
+
+#[hook("/proc/set_gasmix_immutable")]
+fn _set_immutable_hook(mix_val: Value, immutable: Value) {
+	let changed = with_mix_mut(mix_val, |mix| {
+		let before = mix.is_immutable();
+		if immutable.as_number().unwrap_or_default() != 0.0 {
+			mix.mark_immutable();
+		} else {
+			mix.unmark_immutable();
+		}
+		Ok(mix.is_immutable() != before)
+	})?;
+	if changed {
+		turfs::bump_topology_generation();
+	}
+	Ok(Value::null())
+}
+
+/// Sealed sample containers and the like can turn reactions off for just their mix; true turns them back on.
+#[hook("/proc/set_gasmix_reactions")]
+fn _set_gasmix_reactions_hook(mix_val: Value, enabled: Value) {
+	with_mix_mut(mix_val, |mix| {
+		mix.set_reactions_suppressed(enabled.as_number().unwrap_or_default() == 0.0);
+		Ok(Value::null())
 	})
 }
 
@@ -332,21 +793,101 @@ fn _multiply_hook() {
 	})
 }
 
+// Runs a reaction's react proc on `air`, by way of the mix datum, since that's what the proc takes.
+fn react_through(
+	reaction: ReactionIdentifier,
+	mix: &Value,
+	holder: &Value,
+	air: &mut Mixture,
+) -> Result<i32, Runtime> {
+	with_mix_mut(mix, |mix| {
+		mix.copy_from_mutable(air);
+		Ok(())
+	})?;
+	let result = react_by_id(reaction, mix, holder)?
+		.as_number()
+		.unwrap_or_default() as i32;
+	with_mix(mix, |mix| {
+		air.copy_from_mutable(mix);
+		Ok(())
+	})?;
+	Ok(result)
+}
+
 #[hook("/datum/gas_mixture/proc/react")]
 fn _react_hook(holder: Value) {
-	let mut ret: i32 = 0;
-	let reactions = with_mix(src, |mix| Ok(mix.all_reactable()))?;
-	for reaction in reactions {
-		ret |= react_by_id(reaction, src, holder)?
-			.as_number()
-			.unwrap_or_default() as i32;
-		if ret & STOP_REACTIONS == STOP_REACTIONS {
-			return Ok(Value::from(ret as f32));
-		}
+	let (reactions, mut air) = with_mix(src, |mix| Ok((mix.all_reactable(), mix.clone())))?;
+	let (ret, consumed) = run_reactions(
+		&mut air,
+		&reactions,
+		simultaneous_reactions(),
+		|reaction, air| react_through(reaction, src, holder, air),
+	)?;
+	with_mix_mut(src, |mix| {
+		mix.copy_from_mutable(&air);
+		Ok(())
+	})?;
+	for (reaction, amount) in consumed {
+		record_reaction(reaction, amount);
+		dispatch_side_effect(reaction, holder, amount);
 	}
 	Ok(Value::from(ret as f32))
 }
 
+// How many rounds of reactions a preview gets before it's called done, in case something never settles.
+const MAX_PREVIEW_REACTION_ROUNDS: usize = 100;
+
+// Frees a throwaway mix's slot when it goes out of scope, however we leave, instead of whenever
+// byond gets around to deleting the datum. Its pointer gets nulled, so deletion won't free it twice.
+struct TempMixture(Value);
+
+impl Drop for TempMixture {
+	fn drop(&mut self) {
+		GasArena::unregister_many(std::slice::from_ref(&self.0));
+	}
+}
+
+/// Runs reactions to completion on a throwaway copy of the mix and returns what changed:
+/// an associative list of gas id to moles gained (negative if consumed), plus "temperature".
+/// The original mix is never touched and nothing's logged, but the reactions are the DM ones, so their react
+/// procs still run, on the copy with a null holder; ones with effects beyond the mix should check for that.
+/// The copy comes from /proc/__auxtools_new_gas_mixture, which DM needs to define: given a volume, it returns
+/// a new /datum/gas_mixture of that volume.
+#[hook("/proc/preview_reactions")]
+fn _preview_reactions_hook(mix: Value) {
+	let original = with_mix(mix, |air| Ok(air.clone()))?;
+	let temp = TempMixture(gas::new_gas_mixture(original.volume)?);
+	let holder = Value::null();
+	let mut result = original.clone();
+	for _ in 0..MAX_PREVIEW_REACTION_ROUNDS {
+		let reactions = result.all_reactable();
+		let (ret, _) = run_reactions(
+			&mut result,
+			&reactions,
+			simultaneous_reactions(),
+			|reaction, air| react_through(reaction, &temp.0, &holder, air),
+		)?;
+		if ret & REACTING != REACTING {
+			break;
+		}
+	}
+	let deltas: List = List::new();
+	for idx in 0..total_num_gases() {
+		let delta = result.get_moles(idx) - original.get_moles(idx);
+		if delta.abs() > GAS_MIN_MOLES {
+			deltas.set(
+				&Value::from_string(&*gas_idx_to_id(idx)?)?,
+				&Value::from(delta),
+			)?;
+		}
+	}
+	deltas.set(
+		byond_string!("temperature"),
+		result.get_temperature() - original.get_temperature(),
+	)?;
+	Ok(Value::from(deltas))
+}
+
 #[hook("/datum/gas_mixture/proc/adjust_heat")]
 fn _adjust_heat_hook() {
 	with_mix_mut(src, |mix| {
@@ -397,6 +938,116 @@ fn _transfer_ratio_hook(other: Value, ratio: Value) {
 	})
 }
 
+#[hook("/proc/leak_gas")]
+fn _leak_hook(src_mix: Value, dst: Value, conductance: Value) {
+	with_mixes_mut(src_mix, dst, |our_mix, other_mix| {
+		Ok(Value::from(our_mix.leak_into(
+			other_mix,
+			conductance.as_number().map_err(|_| {
+				runtime!(
+					"Attempt to interpret non-number value as number {} {}:{}",
+					std::file!(),
+					std::line!(),
+					std::column!()
+				)
+			})?,
+		)))
+	})
+}
+
+/// Vents the mix out to space through an orifice of the given conductance, with choked flow at high pressure ratios.
+/// Returns how many moles were lost.
+#[hook("/proc/vent_to_space")]
+fn _vent_to_space_hook(mix: Value, conductance_val: Value, space_pressure_val: Value) {
+	let conductance = conductance_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	let space_pressure = space_pressure_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})? / pressure_scale();
+	with_mix_mut(mix, |air| {
+		Ok(Value::from(air.vent_to_space(conductance, space_pressure)))
+	})
+}
+
+/// Moves gas from source to destination like leak_gas, but only if the source is at the higher pressure.
+#[hook("/proc/valve_transfer")]
+fn _valve_transfer_hook(source: Value, destination: Value, conductance: Value) {
+	let conductance = conductance.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	with_mixes_mut(source, destination, |source_mix, destination_mix| {
+		Ok(Value::from(
+			source_mix.valve_into(destination_mix, conductance),
+		))
+	})
+}
+
+/// Moves the mix rate of the way toward the target's gases and temperature, like a recycler working toward a recipe.
+/// If conserve_moles is set, it's the target's composition it goes toward, at whatever moles the mix already has.
+#[hook("/proc/blend_toward")]
+fn _blend_toward_hook(mix: Value, target: Value, rate_val: Value, conserve_moles: Value) {
+	let rate = rate_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	let conserve_moles = conserve_moles.as_number().unwrap_or_default() != 0.0;
+	let target_state = with_mix(target, |air| Ok(air.clone()))?;
+	with_mix_mut(mix, |air| {
+		air.blend_toward(&target_state, rate, conserve_moles);
+		Ok(Value::null())
+	})
+}
+
+/// How much a's temperature would change by if b were merged into it, without merging them.
+#[hook("/proc/mixing_enthalpy")]
+fn _mixing_enthalpy_hook(a: Value, b: Value) {
+	with_mixes(a, b, |a_mix, b_mix| {
+		Ok(Value::from(
+			a_mix.temperature_after_merge(b_mix) - a_mix.get_temperature(),
+		))
+	})
+}
+
+#[hook("/proc/equalize_mixtures")]
+fn _instant_equalize_hook(a: Value, b: Value) {
+	with_mixes_mut(a, b, |our_mix, other_mix| {
+		our_mix.equalize_into(other_mix);
+		Ok(Value::null())
+	})
+}
+
+/// Swaps what's in the two mixes. Pass a true third argument to have each keep its own volume.
+#[hook("/proc/swap_gasmix")]
+fn _swap_with_hook(a: Value, b: Value) {
+	let keep_volume = args
+		.get(2)
+		.map_or(false, |v| v.as_number().unwrap_or_default() != 0.0);
+	with_mixes_mut(a, b, |our_mix, other_mix| {
+		our_mix.swap_contents(other_mix, keep_volume);
+		Ok(Value::null())
+	})
+}
+
 #[hook("/datum/gas_mixture/proc/equalize_with")]
 fn _equalize_with_hook() {
 	with_mixes_custom(
@@ -442,6 +1093,11 @@ fn _oxidation_power_hook(temp: Value) {
 	})
 }
 
+#[hook("/proc/gasmix_is_combustible")]
+fn _gasmix_is_combustible_hook(mix_val: Value) {
+	with_mix(mix_val, |mix| Ok(Value::from(mix.is_combustible())))
+}
+
 #[hook("/proc/equalize_all_gases_in_list")]
 fn _equalize_all_hook() {
 	use std::collections::BTreeSet;
@@ -491,6 +1147,104 @@ fn _equalize_all_hook() {
 	Ok(Value::null())
 }
 
+/// The pressure all the given mixes would settle at if they were connected, without touching any of them.
+/// Zero for an empty list.
+#[hook("/proc/combined_pressure")]
+fn _combined_pressure_hook(list_val: Value) {
+	use std::collections::BTreeSet;
+	let value_list = list_val.as_list().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-list value as list {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	let gas_list: BTreeSet<usize> = (1..=value_list.len())
+		.filter_map(|i| {
+			value_list
+				.get(i)
+				.and_then(|v| v.get_number(byond_string!("_extools_pointer_gasmixture")))
+				.ok()
+				.map(|n| n.to_bits() as usize)
+		})
+		.collect();
+	let pressure = GasArena::with_all_mixtures(|all_mixtures| {
+		let mut tot = gas::Mixture::new();
+		let mut tot_vol = 0.0;
+		for &id in &gas_list {
+			if let Some(src_gas_lock) = all_mixtures.get(id) {
+				let src_gas = src_gas_lock.read();
+				tot.merge(&src_gas);
+				tot_vol += src_gas.volume;
+			}
+		}
+		if tot_vol > 0.0 {
+			tot.volume = tot_vol;
+			tot.return_pressure()
+		} else {
+			0.0
+		}
+	});
+	Ok(Value::from(pressure * pressure_scale()))
+}
+
+// Merges every distinct mix in sources into dst. dst's left out of the sources if it's there, since merging a mix
+// into itself would double it.
+fn merge_all_into(
+	all_mixtures: &[parking_lot::RwLock<gas::Mixture>],
+	sources: &std::collections::BTreeSet<usize>,
+	dst_id: usize,
+) -> Result<(), Runtime> {
+	let mut tot = gas::Mixture::new();
+	for &id in sources.iter().filter(|&&id| id != dst_id) {
+		if let Some(src_gas_lock) = all_mixtures.get(id) {
+			tot.merge(&src_gas_lock.read());
+		}
+	}
+	all_mixtures
+		.get(dst_id)
+		.ok_or_else(|| runtime!("No gas mixture with ID {} exists!", dst_id))?
+		.write()
+		.merge(&tot);
+	Ok(())
+}
+
+#[hook("/proc/merge_all")]
+fn _merge_all_hook(list_val: Value, dst: Value) {
+	use std::collections::BTreeSet;
+	let value_list = list_val.as_list().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-list value as list {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	let dst_id = dst
+		.get_number(byond_string!("_extools_pointer_gasmixture"))
+		.map_err(|_| {
+			runtime!(
+				"Attempt to interpret non-number value as number {} {}:{}",
+				std::file!(),
+				std::line!(),
+				std::column!()
+			)
+		})?
+		.to_bits() as usize;
+	let gas_list: BTreeSet<usize> = (1..=value_list.len())
+		.filter_map(|i| {
+			value_list
+				.get(i)
+				.and_then(|v| v.get_number(byond_string!("_extools_pointer_gasmixture")))
+				.ok()
+				.map(|n| n.to_bits() as usize)
+		})
+		.collect();
+	GasArena::with_all_mixtures(|all_mixtures| merge_all_into(all_mixtures, &gas_list, dst_id))?;
+	Ok(Value::null())
+}
+
 #[hook("/datum/controller/subsystem/air/proc/get_amt_gas_mixes")]
 fn _hook_amt_gas_mixes() {
 	Ok(Value::from(amt_gases() as f32))
@@ -500,3 +1254,73 @@ fn _hook_amt_gas_mixes() {
 fn _hook_max_gas_mixes() {
 	Ok(Value::from(tot_gases() as f32))
 }
+
+/// Makes the mix refill toward the target mix's current contents every tick, like an infinite supply tank.
+/// The target's copied now, so changing or deleting it afterward does nothing.
+#[hook("/proc/register_gas_source")]
+fn _register_gas_source_hook(mix: Value, target: Value) {
+	let target_state = with_mix(target, |air| Ok(air.clone()))?;
+	register_gas_source(mix, target_state)?;
+	Ok(Value::null())
+}
+
+#[hook("/proc/unregister_gas_source")]
+fn _unregister_gas_source_hook(mix: Value) {
+	Ok(Value::from(unregister_gas_source(mix)))
+}
+
+/// Anything over this many moles gets flagged by audit_all_mixtures if it isn't given a cap of its own.
+const AUDIT_MAX_MOLES: f32 = 1.0e7;
+
+/// Finds every mix in use that's broken somehow, as a flat list of pool index then what's wrong with it.
+/// Doesn't fix anything; that's up to whoever's looking.
+#[hook("/proc/audit_all_mixtures")]
+fn _hook_audit_all_mixtures() {
+	let max_moles = args
+		.get(0)
+		.and_then(|v| v.as_number().ok())
+		.unwrap_or(AUDIT_MAX_MOLES);
+	let mut problems = Vec::new();
+	GasArena::for_each_live(|i, mix| {
+		if let Some(problem) = mix.audit(max_moles) {
+			problems.push((i, problem));
+		}
+	});
+	let ret = List::new();
+	for (i, problem) in problems {
+		ret.append(Value::from(i as f32));
+		ret.append(Value::from_string(problem)?);
+	}
+	Ok(Value::from(ret))
+}
+
+#[hook("/proc/gas_pool_headroom")]
+fn _hook_gas_pool_headroom() {
+	Ok(Value::from(gas_pool_headroom() as f32))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_merge_all_into() {
+		use std::collections::BTreeSet;
+		gas::init_test_gases();
+		let all_mixtures = (0..3)
+			.map(|i| {
+				let mut mix = gas::Mixture::new();
+				mix.set_moles(0, 10.0 * (i + 1) as f32);
+				mix.set_temperature(gas::constants::T20C);
+				parking_lot::RwLock::new(mix)
+			})
+			.collect::<Vec<_>>();
+		// Listing a mix twice only merges it once, and dst being in the list doesn't merge it into itself.
+		let sources: BTreeSet<usize> = [1, 1, 2, 0].iter().copied().collect();
+		merge_all_into(&all_mixtures, &sources, 0).unwrap();
+		assert_eq!(all_mixtures[0].read().get_moles(0), 60.0);
+		assert_eq!(all_mixtures[1].read().get_moles(0), 20.0);
+		assert_eq!(all_mixtures[2].read().get_moles(0), 30.0);
+		assert!(merge_all_into(&all_mixtures, &sources, 3).is_err());
+	}
+}
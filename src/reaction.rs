@@ -5,10 +5,226 @@ use auxtools::*;
 
 use std::cell::RefCell;
 
-use crate::gas::{gas_idx_to_id, total_num_gases, GasIDX, Mixture};
+use crate::gas::{
+	gas_idx_from_value, gas_idx_to_id, total_num_gases, with_gas_info, with_mix_mut,
+	with_reactions, FireInfo, GasIDX, Mixture,
+};
+
+use crate::gas::constants::{REACTING, STOP_REACTIONS};
+
+use auxcallback::byond_callback_sender;
 
 use core::cmp::Ordering;
 
+use std::sync::atomic::{self, AtomicBool, AtomicU32, AtomicU64};
+
+use dashmap::DashMap;
+
+use fxhash::FxBuildHasher;
+
+// If set, every reaction a mix can do runs from the same starting state, rather than each seeing what the last left behind.
+static SIMULTANEOUS_REACTIONS: AtomicBool = AtomicBool::new(false);
+
+pub fn simultaneous_reactions() -> bool {
+	SIMULTANEOUS_REACTIONS.load(atomic::Ordering::Relaxed)
+}
+
+/// Sets whether reactions apply one after another in priority order (the default) or all at once.
+/// All at once, each reaction runs against the mix as it was before any reacted, and if they'd use more
+/// of something than there is, every reaction using it gets scaled down to fit.
+#[hook("/proc/set_simultaneous_reactions")]
+fn _set_simultaneous_reactions_hook(simultaneous: Value) {
+	SIMULTANEOUS_REACTIONS.store(
+		simultaneous.as_number().unwrap_or_default() != 0.0,
+		atomic::Ordering::Relaxed,
+	);
+	Ok(Value::null())
+}
+
+// Bits of the f32 below which reactant amounts count as used up. Starts at GAS_MIN_MOLES.
+static RESIDUE_THRESHOLD: AtomicU32 = AtomicU32::new(0x38D1_B717);
+
+pub fn residue_threshold() -> f32 {
+	f32::from_bits(RESIDUE_THRESHOLD.load(atomic::Ordering::Relaxed))
+}
+
+/// Sets how small an amount a reaction can leave behind or use up before it's rounded off; 0 turns rounding off.
+#[hook("/proc/set_reaction_residue_threshold")]
+fn _set_reaction_residue_threshold_hook(threshold_val: Value) {
+	let threshold = threshold_val.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	RESIDUE_THRESHOLD.store(threshold.max(0.0).to_bits(), atomic::Ordering::Relaxed);
+	Ok(Value::null())
+}
+
+/*
+	Keeps reactions from creeping toward zero forever. Any reactant the reaction left less than the threshold of
+	is used up entirely; failing that, if it only used dust of every reactant, it's undone and counts as not having
+	happened. Returns false in that last case. Reactions that don't consume anything are left alone.
+*/
+pub fn settle_reactants(before: &Mixture, after: &mut Mixture, threshold: f32) -> bool {
+	if threshold <= 0.0 {
+		return true;
+	}
+	let consumed = before
+		.enumerate()
+		.filter(|&(idx, amt)| after.get_moles(idx) < amt)
+		.collect::<Vec<_>>();
+	if consumed.is_empty() {
+		return true;
+	}
+	let mut used_up = false;
+	for &(idx, _) in consumed.iter() {
+		if after.get_moles(idx) < threshold {
+			after.set_moles(idx, 0.0);
+			used_up = true;
+		}
+	}
+	if !used_up
+		&& consumed
+			.iter()
+			.all(|&(idx, amt)| amt - after.get_moles(idx) < threshold)
+	{
+		after.copy_from_mutable(before);
+		return false;
+	}
+	true
+}
+
+/// How much of its reactants a reaction used up, going from `before` to `after`. Only gases that went down count,
+/// so whatever it made doesn't offset what it burned.
+pub fn moles_consumed(before: &Mixture, after: &Mixture) -> f32 {
+	before
+		.enumerate()
+		.map(|(idx, amt)| (amt - after.get_moles(idx)).max(0.0))
+		.sum()
+}
+
+/// How much each of `results` has to be scaled down to happen alongside the rest, going from `archive`:
+/// reactions that want more of a gas than there is shrink by whichever of their reactants is the most oversubscribed.
+pub fn simultaneous_scales(archive: &Mixture, results: &[Mixture]) -> Vec<f32> {
+	let num_gases = total_num_gases();
+	let mut demand = vec![0.0_f32; num_gases];
+	for result in results {
+		for (idx, demanded) in demand.iter_mut().enumerate() {
+			*demanded += (archive.get_moles(idx) - result.get_moles(idx)).max(0.0);
+		}
+	}
+	let supply_ratio = |idx: GasIDX| {
+		if demand[idx] > archive.get_moles(idx) {
+			archive.get_moles(idx) / demand[idx]
+		} else {
+			1.0
+		}
+	};
+	results
+		.iter()
+		.map(|result| {
+			(0..num_gases)
+				.filter(|&idx| result.get_moles(idx) < archive.get_moles(idx))
+				.map(supply_ratio)
+				.fold(1.0_f32, f32::min)
+		})
+		.collect()
+}
+
+/// Combines what each reaction did to `archive` on its own into what they'd all do at once,
+/// each scaled by its entry in `scales` (see [`simultaneous_scales`]).
+pub fn combine_simultaneous_reactions(
+	archive: &Mixture,
+	results: &[Mixture],
+	scales: &[f32],
+) -> Mixture {
+	let num_gases = total_num_gases();
+	let mut combined = archive.clone();
+	let initial_energy = archive.thermal_energy();
+	let mut energy = initial_energy;
+	for (result, &scale) in results.iter().zip(scales) {
+		for idx in 0..num_gases {
+			let delta = result.get_moles(idx) - archive.get_moles(idx);
+			if delta != 0.0 {
+				combined.adjust_moles(idx, delta * scale);
+			}
+		}
+		energy += (result.thermal_energy() - initial_energy) * scale;
+	}
+	for idx in 0..num_gases {
+		if combined.get_moles(idx) < 0.0 {
+			combined.set_moles(idx, 0.0);
+		}
+	}
+	combined.garbage_collect();
+	let heat_capacity = combined.heat_capacity();
+	if heat_capacity > 0.0 {
+		combined.set_temperature(energy / heat_capacity);
+	}
+	combined
+}
+
+/*
+	Runs a round of reactions on `mix`. `react` does whatever the given reaction does to the mix it's handed and
+	returns its flags; product scaling and reactant settling go on top of that here. One after another, each
+	reaction sees what the last left behind. All at once, each gets the mix as it started, and they're combined
+	after, scaled down to fit. Returns the flags between them and how much each reaction that went consumed,
+	after any scaling, for the stats and side effects.
+*/
+pub fn run_reactions(
+	mix: &mut Mixture,
+	reactions: &[ReactionIdentifier],
+	simultaneous: bool,
+	mut react: impl FnMut(ReactionIdentifier, &mut Mixture) -> Result<i32, Runtime>,
+) -> Result<(i32, Vec<(ReactionIdentifier, f32)>), Runtime> {
+	let threshold = residue_threshold();
+	let archive = mix.clone();
+	let mut ret = 0;
+	let mut consumed = Vec::new();
+	let mut reacted = Vec::new();
+	let mut results = Vec::new();
+	for &reaction in reactions {
+		let before = if simultaneous {
+			archive.clone()
+		} else {
+			mix.clone()
+		};
+		let mut after = before.clone();
+		let mut result = react(reaction, &mut after)?;
+		if result & REACTING == REACTING {
+			product_scaling(reaction).apply(&before, &mut after);
+			if !settle_reactants(&before, &mut after, threshold) {
+				result &= !REACTING;
+			} else if simultaneous {
+				reacted.push(reaction);
+				results.push(after);
+			} else {
+				consumed.push((reaction, moles_consumed(&before, &after)));
+			}
+		}
+		if !simultaneous {
+			mix.copy_from_mutable(&after);
+		}
+		ret |= result;
+		if ret & STOP_REACTIONS == STOP_REACTIONS {
+			break;
+		}
+	}
+	if !results.is_empty() {
+		let scales = simultaneous_scales(&archive, &results);
+		mix.copy_from_mutable(&combine_simultaneous_reactions(&archive, &results, &scales));
+		consumed.extend(
+			reacted.into_iter().zip(results.iter().zip(scales)).map(
+				|(reaction, (after, scale))| (reaction, moles_consumed(&archive, after) * scale),
+			),
+		);
+	}
+	Ok((ret, consumed))
+}
+
 #[derive(Clone)]
 pub struct Reaction {
 	id: ReactionIdentifier,
@@ -16,7 +232,78 @@ pub struct Reaction {
 	max_temp_req: Option<f32>,
 	min_ener_req: Option<f32>,
 	min_fire_req: Option<f32>,
+	// In kilopascals, whatever the pressure scale; MIN_PRESSURE and MAX_PRESSURE are read and listed as is.
+	min_pressure_req: Option<f32>,
+	max_pressure_req: Option<f32>,
 	min_gas_reqs: Vec<(GasIDX, f32)>,
+	is_fire: bool,
+	side_effect: Option<String>,
+	product_scaling: ProductScaling,
+}
+
+/// How what a reaction makes grows with how much it reacted. Set by the reaction datum's `product_scaling`
+/// ("linear", "quadratic" or "saturating") and `product_scaling_constant`, and applied after `react` runs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ProductScaling {
+	/// Whatever the reaction proc made, untouched.
+	Linear,
+	/// The square of what was made, over the constant: less than linear below it, more above.
+	Quadratic(f32),
+	/// Linear for small amounts, but never more than the constant no matter how much reacts.
+	Saturating(f32),
+}
+
+impl ProductScaling {
+	fn from_byond_reaction(reaction: &Value) -> Self {
+		let constant = reaction
+			.get_number(byond_string!("product_scaling_constant"))
+			.ok()
+			.filter(|&k| k > 0.0 && k.is_finite());
+		match (
+			reaction
+				.get_string(byond_string!("product_scaling"))
+				.ok()
+				.as_deref(),
+			constant,
+		) {
+			(Some("quadratic"), Some(k)) => ProductScaling::Quadratic(k),
+			(Some("saturating"), Some(k)) => ProductScaling::Saturating(k),
+			_ => ProductScaling::Linear,
+		}
+	}
+	/// How much should've been made, given how much linear stoichiometry made.
+	pub fn scale(&self, produced: f32) -> f32 {
+		match *self {
+			ProductScaling::Linear => produced,
+			ProductScaling::Quadratic(k) => produced * produced / k,
+			ProductScaling::Saturating(k) => k * produced / (k + produced),
+		}
+	}
+	/// Rescales every gas there's more of in `after` than in `before`, the mix before the reaction ran.
+	/// Consumed gases and the temperature are left alone.
+	pub fn apply(&self, before: &Mixture, after: &mut Mixture) {
+		if *self == ProductScaling::Linear {
+			return;
+		}
+		let produced = after
+			.enumerate()
+			.map(|(idx, amt)| (idx, amt - before.get_moles(idx)))
+			.filter(|&(_, gain)| gain > 0.0)
+			.collect::<Vec<_>>();
+		for (idx, gain) in produced {
+			after.set_moles(idx, before.get_moles(idx) + self.scale(gain));
+		}
+	}
+}
+
+/// The product scaling of the reaction with the given id, linear if there's no such reaction.
+pub fn product_scaling(id: ReactionIdentifier) -> ProductScaling {
+	with_reactions(|reactions| {
+		reactions
+			.iter()
+			.find(|r| r.get_id() == id)
+			.map_or(ProductScaling::Linear, |r| r.product_scaling)
+	})
 }
 
 #[derive(Copy, Clone)]
@@ -69,9 +356,31 @@ impl Eq for Reaction {}
 
 use std::collections::BTreeMap;
 
+#[derive(Default)]
+struct ReactionStats {
+	times_reacted: AtomicU64,
+	// f64 bits
+	moles_consumed: AtomicU64,
+}
+
 thread_local! {
 	// gotta be a BTreeMap for priorities
-	static REACTION_VALUES: RefCell<BTreeMap<ReactionIdentifier,Value>> = RefCell::new(BTreeMap::new())
+	static REACTION_VALUES: RefCell<BTreeMap<ReactionIdentifier,Value>> = RefCell::new(BTreeMap::new());
+}
+
+// Keyed by the id's hash, so it doesn't matter which thread a reaction happens on.
+static mut REACTION_STATS: Option<DashMap<u64, ReactionStats, FxBuildHasher>> = None;
+
+fn reaction_stats() -> &'static DashMap<u64, ReactionStats, FxBuildHasher> {
+	unsafe { REACTION_STATS.as_ref().unwrap() }
+}
+
+#[init(partial)]
+fn _initialize_reaction_stats() -> Result<(), String> {
+	unsafe {
+		REACTION_STATS = Some(DashMap::with_hasher(FxBuildHasher::default()));
+	};
+	Ok(())
 }
 
 #[shutdown]
@@ -79,6 +388,194 @@ fn clean_up_reaction_values() {
 	REACTION_VALUES.with(|reaction_values| {
 		reaction_values.borrow_mut().clear();
 	});
+	unsafe {
+		REACTION_STATS = None;
+	};
+}
+
+/// Notes down that a reaction happened, for balancing purposes. `consumed` is from [`moles_consumed`].
+pub fn record_reaction(id: ReactionIdentifier, consumed: f32) {
+	let entry = reaction_stats().entry(id.string_id_hash).or_default();
+	entry.times_reacted.fetch_add(1, atomic::Ordering::Relaxed);
+	let _ = entry.moles_consumed.fetch_update(
+		atomic::Ordering::Relaxed,
+		atomic::Ordering::Relaxed,
+		|bits| Some((f64::from_bits(bits) + consumed as f64).to_bits()),
+	);
+}
+
+/// Queues up the reaction's side effect proc, if it has one, with how much it reacted.
+/// Goes through the callback queue so that nothing it does can run while gases are locked.
+pub fn dispatch_side_effect(id: ReactionIdentifier, holder: &Value, amount: f32) {
+	if let Some(proc_name) = with_reactions(|reactions| {
+		reactions
+			.iter()
+			.find(|r| r.get_id() == id)
+			.and_then(|r| r.side_effect().map(str::to_owned))
+	}) {
+		let turf_id = (holder.raw.tag == raw_types::values::ValueTag::Turf)
+			.then(|| unsafe { holder.raw.data.id });
+		send_callback!(byond_callback_sender(), move || {
+			let holder =
+				turf_id.map_or_else(Value::null, |id| unsafe { Value::turf_by_id_unchecked(id) });
+			REACTION_VALUES.with(|r| {
+				r.borrow().get(&id).map_or(Ok(Value::null()), |reaction| {
+					reaction.call(proc_name.as_str(), &[&holder, &Value::from(amount)])
+				})
+			})
+		});
+	}
+}
+
+/// Hands a freshly registered mixture holding `products` to the reaction's `effect_proc`, for smoke, foam and such.
+/// Only byond can make datums, so the mixture itself gets made in the callback.
+pub fn spawn_effect_mixture(id: ReactionIdentifier, holder: &Value, products: Mixture) {
+	let turf_id = (holder.raw.tag == raw_types::values::ValueTag::Turf)
+		.then(|| unsafe { holder.raw.data.id });
+	send_callback!(byond_callback_sender(), move || {
+		let holder =
+			turf_id.map_or_else(Value::null, |id| unsafe { Value::turf_by_id_unchecked(id) });
+		let reaction = REACTION_VALUES
+			.with(|r| r.borrow().get(&id).cloned())
+			.ok_or_else(|| runtime!("Reaction with invalid id"))?;
+		let proc_name = reaction.get_string(byond_string!("effect_proc"))?;
+		let mix = crate::gas::new_gas_mixture(products.volume)?;
+		with_mix_mut(&mix, |air| {
+			air.copy_from_mutable(&products);
+			Ok(())
+		})?;
+		reaction.call(proc_name.as_str(), &[&holder, &mix])
+	});
+}
+
+#[hook("/datum/gas_reaction/proc/spawn_effect_mixture")]
+fn _spawn_effect_mixture_hook(holder: Value, products: Value, temperature: Value) {
+	let id = ReactionIdentifier {
+		string_id_hash: fxhash::hash64(src.get_string(byond_string!("id"))?.as_bytes()),
+		priority: src.get_number(byond_string!("priority"))?,
+	};
+	let products_list = products.as_list().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-list value as list {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	let mut mix = Mixture::new();
+	for i in 1..=products_list.len() {
+		let gas = products_list.get(i)?;
+		let idx = gas_idx_from_value(&gas)?;
+		let amount = products_list.get(gas)?.as_number().map_err(|_| {
+			runtime!(
+				"Attempt to interpret non-number value as number {} {}:{}",
+				std::file!(),
+				std::line!(),
+				std::column!()
+			)
+		})?;
+		mix.set_moles(idx, amount);
+	}
+	mix.set_temperature(temperature.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?);
+	spawn_effect_mixture(id, holder, mix);
+	Ok(Value::null())
+}
+
+pub fn clear_reaction_stats() {
+	reaction_stats().clear();
+}
+
+#[hook("/proc/reaction_stats")]
+fn _reaction_stats_hook() {
+	let ret = List::new();
+	REACTION_VALUES.with(|reaction_values| -> Result<(), Runtime> {
+		for (id, reaction) in reaction_values.borrow().iter() {
+			if let Some(stat) = reaction_stats().get(&id.string_id_hash) {
+				let entry = List::new();
+				entry.set(
+					byond_string!("times_reacted"),
+					stat.times_reacted.load(atomic::Ordering::Relaxed) as f32,
+				)?;
+				entry.set(
+					byond_string!("moles_consumed"),
+					f64::from_bits(stat.moles_consumed.load(atomic::Ordering::Relaxed)) as f32,
+				)?;
+				ret.set(&reaction.get(byond_string!("id"))?, Value::from(entry))?;
+			}
+		}
+		Ok(())
+	})?;
+	Ok(Value::from(ret))
+}
+
+/// What auxmos made of each reaction it loaded, in the order it tries them: the id, priority and parsed requirements,
+/// keyed the same way as `min_requirements`. Products come from the reaction's `products` var, a list of gas to moles,
+/// if it has one; plenty only make theirs inside `react`, so an empty list doesn't mean it makes nothing.
+#[hook("/proc/loaded_reactions")]
+fn _loaded_reactions_hook() {
+	let ret = List::new();
+	REACTION_VALUES.with(|reaction_values| {
+		let reaction_values = reaction_values.borrow();
+		with_reactions(|reactions| -> Result<(), Runtime> {
+			for reaction in reactions.iter() {
+				let entry = List::new();
+				let products = List::new();
+				if let Some(datum) = reaction_values.get(&reaction.get_id()) {
+					entry.set(byond_string!("id"), datum.get(byond_string!("id"))?)?;
+					if let Ok(products_list) = datum.get_list(byond_string!("products")) {
+						for i in 1..=products_list.len() {
+							let gas = products_list.get(i)?;
+							let idx = match gas_idx_from_value(&gas) {
+								Ok(idx) => idx,
+								Err(_) => continue,
+							};
+							if let Some(amount) =
+								products_list.get(gas).ok().and_then(|v| v.as_number().ok())
+							{
+								products.set(Value::from_string(&*gas_idx_to_id(idx)?)?, amount)?;
+							}
+						}
+					}
+				}
+				entry.set(byond_string!("priority"), reaction.get_priority())?;
+				let reqs = List::new();
+				for &(idx, amount) in reaction.min_gas_reqs.iter() {
+					reqs.set(Value::from_string(&*gas_idx_to_id(idx)?)?, amount)?;
+				}
+				if let Some(temp) = reaction.min_temp_req {
+					reqs.set(byond_string!("TEMP"), temp)?;
+				}
+				if let Some(temp) = reaction.max_temp_req {
+					reqs.set(byond_string!("MAX_TEMP"), temp)?;
+				}
+				if let Some(ener) = reaction.min_ener_req {
+					reqs.set(byond_string!("ENER"), ener)?;
+				}
+				if let Some(fire) = reaction.min_fire_req {
+					reqs.set(byond_string!("FIRE_REAGENTS"), fire)?;
+				}
+				if let Some(pressure) = reaction.min_pressure_req {
+					reqs.set(byond_string!("MIN_PRESSURE"), pressure)?;
+				}
+				if let Some(pressure) = reaction.max_pressure_req {
+					reqs.set(byond_string!("MAX_PRESSURE"), pressure)?;
+				}
+				entry.set(byond_string!("requirements"), Value::from(reqs))?;
+				entry.set(byond_string!("products"), Value::from(products))?;
+				entry.set(byond_string!("is_fire"), Value::from(reaction.is_fire()))?;
+				ret.append(Value::from(entry));
+			}
+			Ok(())
+		})
+	})?;
+	Ok(Value::from(ret))
 }
 
 pub fn react_by_id(id: ReactionIdentifier, src: &Value, holder: &Value) -> DMResult {
@@ -90,6 +587,60 @@ pub fn react_by_id(id: ReactionIdentifier, src: &Value, holder: &Value) -> DMRes
 	})
 }
 
+// A reaction is a fire if it needs both something to burn and something to burn it with.
+fn requires_fuel_and_oxidizer(gas_reqs: &[(GasIDX, f32)]) -> bool {
+	with_gas_info(|gas_info| {
+		let (mut fuel, mut oxidizer) = (false, false);
+		for &(idx, _) in gas_reqs {
+			match gas_info.get(idx).map(|gas| gas.fire_info) {
+				Some(FireInfo::Fuel(_)) => fuel = true,
+				Some(FireInfo::Oxidation(_)) => oxidizer = true,
+				_ => (),
+			}
+		}
+		fuel && oxidizer
+	})
+}
+
+/// What a mix is missing before a reaction can happen in it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum IgnitionGap {
+	/// The reaction's short this many moles of this gas.
+	Moles(GasIDX, f32),
+	/// The mix doesn't have enough fire reagents (fuel or oxidizer, whichever's scarcer) by this many.
+	FireReagents(f32),
+	/// The mix's pressure is out of the reaction's range by this many kilopascals; negative if it's too high.
+	Pressure(f32),
+	/// The mix has everything it needs but has to get this many kelvins hotter.
+	Temperature(f32),
+}
+
+impl IgnitionGap {
+	// A mix that only needs heating is closer to going than one that needs more gas; otherwise, smaller gaps are closer.
+	fn closeness_key(&self) -> (u8, float_ord::FloatOrd<f32>) {
+		match *self {
+			Self::Temperature(gap) => (0, float_ord::FloatOrd(gap)),
+			Self::FireReagents(gap) => (1, float_ord::FloatOrd(gap)),
+			Self::Moles(_, gap) => (1, float_ord::FloatOrd(gap)),
+			Self::Pressure(gap) => (1, float_ord::FloatOrd(gap.abs())),
+		}
+	}
+}
+
+/// What's keeping the mix from catching fire, going by whichever fire reaction it's closest to being able to do.
+/// None if it can already burn, or if no amount of gas or heat would light it.
+pub fn ignition_gap(mix: &Mixture) -> Option<IgnitionGap> {
+	with_reactions(|reactions| {
+		let fires = reactions.iter().filter(|r| r.is_fire());
+		if fires.clone().any(|r| r.check_conditions(mix)) {
+			return None;
+		}
+		fires
+			.filter_map(|r| r.ignition_gap(mix))
+			.min_by_key(IgnitionGap::closeness_key)
+	})
+}
+
 impl Reaction {
 	/// Takes a `/datum/gas_reaction` and makes a byond reaction out of it.
 	///
@@ -105,6 +656,11 @@ impl Reaction {
 			string_id_hash,
 			priority,
 		};
+		let side_effect = reaction
+			.get_string(byond_string!("side_effect_proc"))
+			.ok()
+			.filter(|name| !name.is_empty());
+		let product_scaling = ProductScaling::from_byond_reaction(reaction);
 		let our_reaction = {
 			if let Ok(min_reqs) = reaction.get_list(byond_string!("min_requirements")) {
 				let mut min_gas_reqs: Vec<(GasIDX, f32)> = Vec::new();
@@ -133,13 +689,27 @@ impl Reaction {
 					.get(byond_string!("FIRE_REAGENTS"))
 					.and_then(|v| v.as_number())
 					.ok();
+				let min_pressure_req = min_reqs
+					.get(byond_string!("MIN_PRESSURE"))
+					.and_then(|v| v.as_number())
+					.ok();
+				let max_pressure_req = min_reqs
+					.get(byond_string!("MAX_PRESSURE"))
+					.and_then(|v| v.as_number())
+					.ok();
+				let is_fire = min_fire_req.is_some() || requires_fuel_and_oxidizer(&min_gas_reqs);
 				Reaction {
 					id,
 					min_temp_req,
 					max_temp_req,
 					min_ener_req,
 					min_fire_req,
+					min_pressure_req,
+					max_pressure_req,
 					min_gas_reqs,
+					is_fire,
+					side_effect,
+					product_scaling,
 				}
 			} else {
 				Reaction {
@@ -148,7 +718,12 @@ impl Reaction {
 					max_temp_req: Some(1.0),
 					min_ener_req: None,
 					min_fire_req: None,
+					min_pressure_req: None,
+					max_pressure_req: None,
 					min_gas_reqs: vec![],
+					is_fire: false,
+					side_effect,
+					product_scaling,
 				}
 			}
 		};
@@ -172,11 +747,78 @@ impl Reaction {
 			&& self
 				.min_ener_req
 				.map_or(true, |ener_req| mix.thermal_energy() >= ener_req)
+			&& self.pressure_in_range(mix)
 			&& self.min_fire_req.map_or(true, |fire_req| {
 				let (oxi, fuel) = mix.get_burnability();
 				oxi.min(fuel) >= fire_req
 			})
 	}
+	// Only works out the pressure if there's a pressure requirement at all.
+	fn pressure_in_range(&self, mix: &Mixture) -> bool {
+		if self.min_pressure_req.is_none() && self.max_pressure_req.is_none() {
+			return true;
+		}
+		let pressure = mix.return_pressure();
+		self.min_pressure_req.map_or(true, |req| pressure >= req)
+			&& self.max_pressure_req.map_or(true, |req| pressure <= req)
+	}
+	/// The biggest shortfall of any one gas keeping this reaction from happening in the mix, or failing that,
+	/// how far off its pressure or temperature is. None if it can already happen or would never, e.g. if it's too hot.
+	pub fn ignition_gap(&self, mix: &Mixture) -> Option<IgnitionGap> {
+		if self.check_conditions(mix) {
+			return None;
+		}
+		if let Some((idx, gap)) = self
+			.min_gas_reqs
+			.iter()
+			.map(|&(idx, req)| (idx, req - mix.get_moles(idx)))
+			.filter(|&(_, gap)| gap > 0.0)
+			.max_by_key(|&(_, gap)| float_ord::FloatOrd(gap))
+		{
+			return Some(IgnitionGap::Moles(idx, gap));
+		}
+		if let Some(fire_req) = self.min_fire_req {
+			let (oxi, fuel) = mix.get_burnability();
+			if oxi.min(fuel) < fire_req {
+				return Some(IgnitionGap::FireReagents(fire_req - oxi.min(fuel)));
+			}
+		}
+		if !self.pressure_in_range(mix) {
+			let pressure = mix.return_pressure();
+			return Some(IgnitionGap::Pressure(match self.min_pressure_req {
+				Some(req) if pressure < req => req - pressure,
+				_ => self.max_pressure_req.map_or(0.0, |req| req - pressure),
+			}));
+		}
+		let heat_capacity = mix.heat_capacity();
+		let energy_temp = self.min_ener_req.map(|ener_req| {
+			if heat_capacity > 0.0 {
+				ener_req / heat_capacity
+			} else {
+				f32::INFINITY
+			}
+		});
+		let needed_temp = self
+			.min_temp_req
+			.unwrap_or(0.0)
+			.max(energy_temp.unwrap_or(0.0));
+		let temperature = mix.get_temperature();
+		if !needed_temp.is_finite()
+			|| self.max_temp_req.map_or(false, |max_temp| {
+				temperature > max_temp || needed_temp > max_temp
+			}) {
+			return None;
+		}
+		(needed_temp > temperature).then(|| IgnitionGap::Temperature(needed_temp - temperature))
+	}
+	/// Whether this is a fire--it either wants fire reagents or needs both a fuel and an oxidizer.
+	pub fn is_fire(&self) -> bool {
+		self.is_fire
+	}
+	/// The name of the proc on the reaction datum to call after it reacts, if any.
+	pub fn side_effect(&self) -> Option<&str> {
+		self.side_effect.as_deref()
+	}
 	/// Returns the priority of the reaction.
 	pub fn get_priority(&self) -> f32 {
 		self.id.priority
@@ -186,3 +828,224 @@ impl Reaction {
 		react_by_id(self.id, src, holder)
 	}
 }
+
+/// The reactions loaded for unit tests: just a plasma fire, requirements as on Citadel.
+#[cfg(test)]
+pub(crate) fn test_reactions() -> Vec<Reaction> {
+	use crate::gas::{constants::*, gas_idx_from_string};
+	let min_gas_reqs = vec![
+		(gas_idx_from_string(GAS_PLASMA).unwrap(), 0.01),
+		(gas_idx_from_string(GAS_O2).unwrap(), 0.01),
+	];
+	vec![Reaction {
+		id: ReactionIdentifier {
+			string_id_hash: fxhash::hash64("plasmafire".as_bytes()),
+			priority: -2.0,
+		},
+		min_temp_req: Some(FIRE_MINIMUM_TEMPERATURE_TO_EXIST),
+		max_temp_req: None,
+		min_ener_req: None,
+		min_fire_req: None,
+		min_pressure_req: None,
+		max_pressure_req: None,
+		is_fire: requires_fuel_and_oxidizer(&min_gas_reqs),
+		min_gas_reqs,
+		side_effect: None,
+		product_scaling: ProductScaling::Linear,
+	}]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_energy_requirement() {
+		crate::gas::init_test_gases();
+		let reaction = Reaction {
+			id: ReactionIdentifier {
+				string_id_hash: fxhash::hash64("energy_test".as_bytes()),
+				priority: 0.0,
+			},
+			min_temp_req: Some(1000.0),
+			max_temp_req: None,
+			min_ener_req: Some(1_000_000.0),
+			min_fire_req: None,
+			min_pressure_req: None,
+			max_pressure_req: None,
+			min_gas_reqs: vec![],
+			is_fire: false,
+			side_effect: None,
+			product_scaling: ProductScaling::Linear,
+		};
+		let mut mix = Mixture::new();
+		mix.set_moles(0, 1.0);
+		mix.set_temperature(5000.0);
+		// hot enough, but 20 heat capacity at 5000K is only 100,000 joules
+		assert!(!reaction.check_conditions(&mix));
+		mix.set_moles(0, 100.0);
+		assert!(reaction.check_conditions(&mix));
+	}
+	#[test]
+	fn test_pressure_requirement() {
+		use crate::gas::constants::*;
+		crate::gas::init_test_gases();
+		let mut reaction = test_reactions().remove(0);
+		reaction.min_pressure_req = Some(ONE_ATMOSPHERE);
+		let mut mix = Mixture::from_vol(CELL_VOLUME);
+		mix.set_moles(0, 1.0);
+		mix.set_moles(3, 1.0);
+		mix.set_temperature(FIRE_MINIMUM_TEMPERATURE_TO_EXIST + 100.0);
+		// hot enough and enough of everything, but nowhere near an atmosphere
+		let pressure = mix.return_pressure();
+		assert!(pressure < ONE_ATMOSPHERE);
+		assert!(!reaction.check_conditions(&mix));
+		match reaction.ignition_gap(&mix) {
+			Some(IgnitionGap::Pressure(gap)) => {
+				assert!((gap - (ONE_ATMOSPHERE - pressure)).abs() < 0.01)
+			}
+			_ => panic!("a thin mix should just need more pressure"),
+		}
+		mix.set_moles(0, 100.0);
+		assert!(reaction.check_conditions(&mix));
+		reaction.max_pressure_req = Some(ONE_ATMOSPHERE * 1.5);
+		assert!(!reaction.check_conditions(&mix));
+		match reaction.ignition_gap(&mix) {
+			Some(IgnitionGap::Pressure(gap)) => assert!(gap < 0.0),
+			_ => panic!("a dense mix should need less pressure"),
+		}
+	}
+	#[test]
+	fn test_settle_reactants() {
+		crate::gas::init_test_gases();
+		// a reaction burning off half its plasma each time would never quite get to zero on its own
+		let mut mix = Mixture::new();
+		mix.set_moles(3, 1.0);
+		mix.set_moles(0, 100.0);
+		let mut rounds = 0;
+		loop {
+			rounds += 1;
+			assert!(rounds < 100, "reaction never settled");
+			let before = mix.clone();
+			let plasma = mix.get_moles(3);
+			mix.set_moles(3, plasma * 0.5);
+			mix.set_moles(0, mix.get_moles(0) - plasma * 0.5);
+			if !settle_reactants(&before, &mut mix, 0.001) || mix.get_moles(3) == 0.0 {
+				break;
+			}
+		}
+		assert_eq!(mix.get_moles(3), 0.0);
+		// dust reactions get undone, and nothing changes with rounding off
+		let mut before = Mixture::new();
+		before.set_moles(0, 10.0);
+		before.set_moles(3, 10.0);
+		let mut after = before.clone();
+		after.set_moles(3, 9.9995);
+		assert!(!settle_reactants(&before, &mut after, 0.001));
+		assert_eq!(after.get_moles(3), 10.0);
+		after.set_moles(3, 9.9995);
+		assert!(settle_reactants(&before, &mut after, 0.0));
+		assert_eq!(after.get_moles(3), 9.9995);
+	}
+	#[test]
+	fn test_moles_consumed() {
+		crate::gas::init_test_gases();
+		let mut before = Mixture::new();
+		before.set_moles(0, 10.0);
+		before.set_moles(3, 10.0);
+		let mut after = before.clone();
+		// burns 4 plasma and 2 oxygen into 6 co2; the total doesn't move, but 6 moles were used up
+		after.adjust_moles(3, -4.0);
+		after.adjust_moles(0, -2.0);
+		after.adjust_moles(2, 6.0);
+		assert!((before.total_moles() - after.total_moles()).abs() < 0.001);
+		assert!((moles_consumed(&before, &after) - 6.0).abs() < 0.001);
+	}
+	#[test]
+	fn test_product_scaling() {
+		crate::gas::init_test_gases();
+		let mut before = Mixture::new();
+		before.set_moles(0, 50.0);
+		before.set_moles(2, 10.0);
+		// burned 20 oxygen into 20 co2
+		let mut after = before.clone();
+		after.set_moles(0, 30.0);
+		after.set_moles(2, 30.0);
+		let mut linear = after.clone();
+		ProductScaling::Linear.apply(&before, &mut linear);
+		assert_eq!(linear.get_moles(2), 30.0);
+		let mut quadratic = after.clone();
+		ProductScaling::Quadratic(5.0).apply(&before, &mut quadratic);
+		assert!((quadratic.get_moles(2) - 90.0).abs() < 0.001);
+		assert_eq!(quadratic.get_moles(0), 30.0);
+		let mut saturating = after.clone();
+		ProductScaling::Saturating(20.0).apply(&before, &mut saturating);
+		assert!((saturating.get_moles(2) - 20.0).abs() < 0.001);
+		assert_eq!(saturating.get_moles(0), 30.0);
+		// saturating never makes more than its constant
+		assert!(ProductScaling::Saturating(20.0).scale(1.0e6) < 20.0);
+	}
+	#[test]
+	fn test_ignition_gap() {
+		use crate::gas::constants::*;
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		mix.set_moles(3, 20.0);
+		mix.set_temperature(FIRE_MINIMUM_TEMPERATURE_TO_EXIST + 100.0);
+		match ignition_gap(&mix) {
+			Some(IgnitionGap::Moles(0, gap)) => assert!((gap - 0.01).abs() < 0.0001),
+			_ => panic!("plasma with no oxygen should be short on oxygen"),
+		}
+		mix.set_moles(0, 20.0);
+		assert_eq!(ignition_gap(&mix), None);
+		mix.set_temperature(FIRE_MINIMUM_TEMPERATURE_TO_EXIST - 50.0);
+		match ignition_gap(&mix) {
+			Some(IgnitionGap::Temperature(gap)) => assert!((gap - 50.0).abs() < 0.01),
+			_ => panic!("a cold fuel mix should just need heating"),
+		}
+	}
+	#[test]
+	fn test_simultaneous_reactions() {
+		use crate::gas::constants::*;
+		crate::gas::init_test_gases();
+		// both of these burn up to 10 moles of plasma, into co2 and n2 respectively
+		let to_co2 = ReactionIdentifier {
+			string_id_hash: 1,
+			priority: 1.0,
+		};
+		let to_n2 = ReactionIdentifier {
+			string_id_hash: 2,
+			priority: 0.0,
+		};
+		let burn = |reaction: ReactionIdentifier, mix: &mut Mixture| -> Result<i32, Runtime> {
+			let product = if reaction == to_co2 { 2 } else { 1 };
+			let burned = mix.get_moles(3).min(10.0);
+			mix.adjust_moles(3, -burned);
+			mix.adjust_moles(product, burned);
+			Ok(if burned > 0.0 { REACTING } else { 0 })
+		};
+		let mut archive = Mixture::new();
+		archive.set_moles(3, 10.0);
+		archive.set_temperature(T20C);
+		let mut sequential = archive.clone();
+		let (ret, consumed) =
+			run_reactions(&mut sequential, &[to_co2, to_n2], false, burn).unwrap();
+		assert_eq!(ret, REACTING);
+		assert_eq!(sequential.get_moles(2), 10.0);
+		assert_eq!(sequential.get_moles(1), 0.0);
+		assert_eq!(consumed.len(), 1);
+		assert!(consumed[0].0 == to_co2 && consumed[0].1 == 10.0);
+		let mut simultaneous = archive.clone();
+		let (ret, consumed) =
+			run_reactions(&mut simultaneous, &[to_co2, to_n2], true, burn).unwrap();
+		assert_eq!(ret, REACTING);
+		assert!((simultaneous.get_moles(2) - 5.0).abs() < 0.001);
+		assert!((simultaneous.get_moles(1) - 5.0).abs() < 0.001);
+		assert!(simultaneous.get_moles(3).abs() < 0.001);
+		// each only got half the plasma it wanted, and that's what gets counted
+		assert_eq!(consumed.len(), 2);
+		assert!(consumed.iter().all(|&(_, amt)| (amt - 5.0).abs() < 0.001));
+		// the same plasma's gone either way
+		assert!((simultaneous.total_moles() - sequential.total_moles()).abs() < 0.001);
+	}
+}
@@ -1,3 +1,70 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+// Callbacks that couldn't be queued for BYOND, e.g. because the channel was full.
+static DROPPED_CALLBACKS: AtomicU64 = AtomicU64::new(0);
+// If set, callbacks wait for room in the channel instead of being dropped.
+static BLOCKING_CALLBACKS: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn note_dropped_callback() {
+	DROPPED_CALLBACKS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn blocking_callbacks() -> bool {
+	BLOCKING_CALLBACKS.load(Ordering::Relaxed)
+}
+
+thread_local! {
+	// Set for whichever thread initialized us, which is BYOND's.
+	static ON_MAIN_THREAD: std::cell::Cell<bool> = std::cell::Cell::new(false);
+}
+
+pub(crate) fn on_main_thread() -> bool {
+	ON_MAIN_THREAD.with(|on_main| on_main.get())
+}
+
+// Sends a callback to BYOND, counting it if it gets dropped.
+// The main thread's what empties the channel, so it never blocks, even with blocking callbacks on.
+macro_rules! send_callback {
+	($sender:expr, $f:expr) => {{
+		let func = Box::new($f);
+		if $crate::turfs::blocking_callbacks() && !$crate::turfs::on_main_thread() {
+			if $sender.send(func).is_err() {
+				$crate::turfs::note_dropped_callback();
+			}
+		} else if $sender.try_send(func).is_err() {
+			$crate::turfs::note_dropped_callback();
+		}
+	}};
+}
+
+// Reads a number, giving the usual runtime, pointing at the caller, if it isn't one.
+#[track_caller]
+fn number(v: &Value) -> Result<f32, Runtime> {
+	let caller = std::panic::Location::caller();
+	v.as_number().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-number value as number {} {}:{}",
+			caller.file(),
+			caller.line(),
+			caller.column()
+		)
+	})
+}
+
+// The world's maxx and maxy, for working out which turf ids are next to which.
+fn world_dimensions() -> Result<(i32, i32), Runtime> {
+	let world = auxtools::Value::world();
+	Ok((
+		number(&world.get(byond_string!("maxx"))?)? as i32,
+		number(&world.get(byond_string!("maxy"))?)? as i32,
+	))
+}
+
+#[hook("/datum/controller/subsystem/air/proc/get_dropped_callbacks")]
+fn _hook_get_dropped_callbacks() {
+	Ok(Value::from(DROPPED_CALLBACKS.load(Ordering::Relaxed) as f32))
+}
+
 pub mod processing;
 
 #[cfg(feature = "monstermos")]
@@ -6,7 +73,7 @@ pub mod monstermos;
 #[cfg(feature = "putnamos")]
 pub mod putnamos;
 
-use crate::gas::Mixture;
+use crate::gas::{gas_idx_from_value, gas_idx_to_id, with_mix, Mixture};
 
 use auxtools::*;
 
@@ -14,7 +81,9 @@ use crate::constants::*;
 
 use crate::GasArena;
 
-use dashmap::DashMap;
+use auxcallback::byond_callback_sender;
+
+use dashmap::{DashMap, DashSet};
 
 use fxhash::FxBuildHasher;
 
@@ -58,6 +127,14 @@ impl TurfMixture {
 	pub fn enabled(&self) -> bool {
 		self.simulation_level > 0
 			&& self.simulation_level & SIMULATION_LEVEL_DISABLED != SIMULATION_LEVEL_DISABLED
+			&& !self.frozen()
+	}
+	pub fn frozen(&self) -> bool {
+		self.simulation_level & SIMULATION_LEVEL_FROZEN == SIMULATION_LEVEL_FROZEN
+	}
+	// Like enabled, but sleeping turfs count too; they're still part of the air, just not being processed.
+	pub fn simulated(&self) -> bool {
+		self.simulation_level & SIMULATION_LEVEL_ANY != 0 && !self.frozen()
 	}
 	pub fn adjacent_mixes<'a>(
 		&'a self,
@@ -148,6 +225,27 @@ static mut TURF_GASES: Option<DashMap<TurfID, TurfMixture, FxBuildHasher>> = Non
 static mut TURF_TEMPERATURES: Option<DashMap<TurfID, ThermalInfo, FxBuildHasher>> = None;
 // We store planetary atmos by hash of the initial atmos string here for speed.
 static mut PLANETARY_ATMOS: Option<DashMap<u32, Mixture, FxBuildHasher>> = None;
+// Gas mixtures of frozen turfs, so neighbors can treat them as walls without a turf lookup.
+static mut FROZEN_MIXES: Option<DashSet<usize, FxBuildHasher>> = None;
+// Whether the above has anything in it; almost always false, so it's checked first.
+static ANY_FROZEN: AtomicBool = AtomicBool::new(false);
+// The processing tick each turf was last equalized or set up to react on.
+static mut TURF_LAST_PROCESSED: Option<DashMap<TurfID, u64, FxBuildHasher>> = None;
+// The composition hash each turf's air last had, and the processing tick it changed on; only kept while SSair asks for it.
+static AIR_CHANGE_TICKS: parking_lot::RwLock<Option<DashMap<TurfID, (u64, u64), FxBuildHasher>>> =
+	parking_lot::const_rwlock(None);
+// Goes up by one every time the atmos thread starts a tick.
+static PROCESSING_TICK: AtomicU64 = AtomicU64::new(0);
+// Bumped whenever anything that decides how turfs connect changes, so cached zones know they're stale.
+static TOPOLOGY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn topology_generation() -> u64 {
+	TOPOLOGY_GENERATION.load(Ordering::Acquire)
+}
+
+pub(crate) fn bump_topology_generation() {
+	TOPOLOGY_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
 
 #[init(partial)]
 fn _initialize_turf_statics() -> Result<(), String> {
@@ -155,7 +253,10 @@ fn _initialize_turf_statics() -> Result<(), String> {
 		TURF_GASES = Some(DashMap::with_hasher(FxBuildHasher::default()));
 		TURF_TEMPERATURES = Some(DashMap::with_hasher(FxBuildHasher::default()));
 		PLANETARY_ATMOS = Some(DashMap::with_hasher(FxBuildHasher::default()));
+		FROZEN_MIXES = Some(DashSet::with_hasher(FxBuildHasher::default()));
+		TURF_LAST_PROCESSED = Some(DashMap::with_hasher(FxBuildHasher::default()));
 	};
+	ON_MAIN_THREAD.with(|on_main| on_main.set(true));
 	Ok(())
 }
 
@@ -165,7 +266,12 @@ fn _shutdown_turfs() {
 		TURF_GASES = None;
 		TURF_TEMPERATURES = None;
 		PLANETARY_ATMOS = None;
+		FROZEN_MIXES = None;
+		TURF_LAST_PROCESSED = None;
 	};
+	ON_MAIN_THREAD.with(|on_main| on_main.set(false));
+	PROCESSING_TICK.store(0, Ordering::Relaxed);
+	*AIR_CHANGE_TICKS.write() = None;
 }
 // this would lead to undefined info if it were possible for something to put a None on it during operation, but nothing's going to do that
 fn turf_gases() -> &'static DashMap<TurfID, TurfMixture, FxBuildHasher> {
@@ -180,6 +286,57 @@ fn turf_temperatures() -> &'static DashMap<TurfID, ThermalInfo, FxBuildHasher> {
 	unsafe { TURF_TEMPERATURES.as_ref().unwrap() }
 }
 
+fn turf_last_processed() -> &'static DashMap<TurfID, u64, FxBuildHasher> {
+	unsafe { TURF_LAST_PROCESSED.as_ref().unwrap() }
+}
+
+fn advance_processing_tick() {
+	PROCESSING_TICK.fetch_add(1, Ordering::Relaxed);
+}
+
+fn mark_turf_processed(id: TurfID) {
+	turf_last_processed().insert(id, PROCESSING_TICK.load(Ordering::Relaxed));
+}
+
+fn set_tracking_air_changes(enabled: bool) {
+	let mut changes = AIR_CHANGE_TICKS.write();
+	if !enabled {
+		*changes = None;
+	} else if changes.is_none() {
+		*changes = Some(DashMap::with_hasher(FxBuildHasher::default()));
+	}
+}
+
+// Notes the turf's air down as changed this tick if it's not the same as last time it was noted.
+fn note_air_composition(
+	changes: &DashMap<TurfID, (u64, u64), FxBuildHasher>,
+	id: TurfID,
+	mix: &Mixture,
+) {
+	let hash = mix.composition_hash();
+	if changes.get(&id).map_or(true, |entry| entry.0 != hash) {
+		changes.insert(id, (hash, PROCESSING_TICK.load(Ordering::Relaxed)));
+	}
+}
+
+fn frozen_mixes() -> &'static DashSet<usize, FxBuildHasher> {
+	unsafe { FROZEN_MIXES.as_ref().unwrap() }
+}
+
+// Whether the given mix belongs to a frozen turf.
+fn is_frozen_mix(mix: usize) -> bool {
+	ANY_FROZEN.load(Ordering::Relaxed) && frozen_mixes().contains(&mix)
+}
+
+fn set_mix_frozen(mix: usize, frozen: bool) {
+	if frozen {
+		frozen_mixes().insert(mix);
+	} else {
+		frozen_mixes().remove(&mix);
+	}
+	ANY_FROZEN.store(!frozen_mixes().is_empty(), Ordering::Relaxed);
+}
+
 #[hook("/turf/proc/update_air_ref")]
 fn _hook_register_turf() {
 	let simulation_level = args[0].as_number().map_err(|_| {
@@ -191,7 +348,12 @@ fn _hook_register_turf() {
 		)
 	})?;
 	if simulation_level < 0.0 {
-		turf_gases().remove(&unsafe { src.raw.data.id });
+		if let Some((_, old)) = turf_gases().remove(&unsafe { src.raw.data.id }) {
+			if old.frozen() {
+				set_mix_frozen(old.mix, false);
+			}
+		}
+		bump_topology_generation();
 		Ok(Value::null())
 	} else {
 		let mut to_insert: TurfMixture = TurfMixture::default();
@@ -231,7 +393,15 @@ fn _hook_register_turf() {
 			}
 		}
 		let id = unsafe { src.raw.data.id };
+		// Re-registering shouldn't thaw the turf out.
+		let old_turf = turf_gases().get(&id).map(|old| *old);
+		if let Some(old) = old_turf.filter(TurfMixture::frozen) {
+			to_insert.simulation_level |= SIMULATION_LEVEL_FROZEN;
+			set_mix_frozen(old.mix, false);
+			set_mix_frozen(to_insert.mix, true);
+		}
 		turf_gases().insert(id, to_insert);
+		bump_topology_generation();
 		Ok(Value::null())
 	}
 }
@@ -315,22 +485,694 @@ fn _hook_sleep() {
 	} else {
 		0.0
 	};
-	if arg == 0.0 {
-		turf_gases()
-			.entry(unsafe { src.raw.data.id })
-			.and_modify(|turf| {
+	let mut changed = false;
+	turf_gases()
+		.entry(unsafe { src.raw.data.id })
+		.and_modify(|turf| {
+			let before = turf.simulation_level;
+			if arg == 0.0 {
 				turf.simulation_level &= !SIMULATION_LEVEL_DISABLED;
-			});
-	} else {
-		turf_gases()
-			.entry(unsafe { src.raw.data.id })
-			.and_modify(|turf| {
+			} else {
 				turf.simulation_level |= SIMULATION_LEVEL_DISABLED;
-			});
+			}
+			changed = turf.simulation_level != before;
+		});
+	if changed {
+		bump_topology_generation();
 	}
 	Ok(Value::from(true))
 }
 
+// Clears the sleeping flag, so the turf gets processed again.
+fn wake_turf(id: TurfID) {
+	let mut changed = false;
+	turf_gases().entry(id).and_modify(|turf| {
+		let before = turf.simulation_level;
+		turf.simulation_level &= !SIMULATION_LEVEL_DISABLED;
+		changed = turf.simulation_level != before;
+	});
+	if changed {
+		bump_topology_generation();
+	}
+}
+
+// Copies the given template into a turf's air, or empties the air if the template is null.
+fn set_turf_air(
+	id: TurfID,
+	template: Option<&Mixture>,
+	all_mixtures: &[parking_lot::RwLock<Mixture>],
+) -> Result<(), Runtime> {
+	let mix = turf_gases()
+		.get(&id)
+		.ok_or_else(|| runtime!("Attempted to set the air of a turf that has none!"))?
+		.mix;
+	let mut air = all_mixtures
+		.get(mix)
+		.ok_or_else(|| runtime!("No gas mixture with ID {} exists!", mix))?
+		.write();
+	match template {
+		Some(template) => air.copy_from_mutable(template),
+		None => air.clear(),
+	}
+	wake_turf(id);
+	Ok(())
+}
+
+#[hook("/proc/set_turf_air")]
+fn _hook_set_turf_air(turf: Value, template_val: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("set_turf_air called on a non-turf"));
+	}
+	let template = if template_val.raw.tag == raw_types::values::ValueTag::Null {
+		None
+	} else {
+		Some(with_mix(template_val, |template| Ok(template.clone()))?)
+	};
+	GasArena::with_all_mixtures(|all_mixtures| {
+		set_turf_air(unsafe { turf.raw.data.id }, template.as_ref(), all_mixtures)
+	})?;
+	Ok(Value::null())
+}
+
+// For map loading and the like, where there's thousands of these at once.
+#[hook("/proc/bulk_set_turf_air")]
+fn _hook_bulk_set_turf_air(turfs_val: Value, templates_val: Value) {
+	let (turfs, templates) = match (turfs_val.as_list(), templates_val.as_list()) {
+		(Ok(turfs), Ok(templates)) => (turfs, templates),
+		_ => {
+			return Err(runtime!(
+				"Attempt to interpret non-list value as list {} {}:{}",
+				std::file!(),
+				std::line!(),
+				std::column!()
+			))
+		}
+	};
+	if turfs.len() != templates.len() {
+		return Err(runtime!(
+			"Given {} turfs but {} templates to set their air to!",
+			turfs.len(),
+			templates.len()
+		));
+	}
+	let to_set = (1..=turfs.len())
+		.map(|i| {
+			let turf = turfs.get(i)?;
+			let template = templates.get(i)?;
+			let template_idx = if template.raw.tag == raw_types::values::ValueTag::Null {
+				None
+			} else {
+				Some(
+					template
+						.get_number(byond_string!("_extools_pointer_gasmixture"))
+						.map_err(|_| {
+							runtime!(
+								"Attempt to interpret non-number value as number {} {}:{}",
+								std::file!(),
+								std::line!(),
+								std::column!()
+							)
+						})?
+						.to_bits() as usize,
+				)
+			};
+			Ok((unsafe { turf.raw.data.id }, template_idx))
+		})
+		.collect::<Result<Vec<_>, Runtime>>()?;
+	GasArena::with_all_mixtures(|all_mixtures| {
+		for &(id, template_idx) in &to_set {
+			// cloned, in case a turf's being set to its own air
+			let template = template_idx
+				.map(|idx| {
+					all_mixtures
+						.get(idx)
+						.map(|template| template.read().clone())
+						.ok_or_else(|| runtime!("No gas mixture with ID {} exists!", idx))
+				})
+				.transpose()?;
+			set_turf_air(id, template.as_ref(), all_mixtures)?;
+		}
+		Ok(Value::null())
+	})
+}
+
+// Sets every turf in the list to the same air, like filling a whole area at roundstart. Null empties them.
+// Turfs without air are skipped; returns how many were set.
+#[hook("/proc/fill_area_air")]
+fn _hook_fill_area_air(turfs_val: Value, template_val: Value) {
+	let turfs = turfs_val.as_list().map_err(|_| {
+		runtime!(
+			"Attempt to interpret non-list value as list {} {}:{}",
+			std::file!(),
+			std::line!(),
+			std::column!()
+		)
+	})?;
+	let template = if template_val.raw.tag == raw_types::values::ValueTag::Null {
+		None
+	} else {
+		Some(with_mix(template_val, |template| Ok(template.clone()))?)
+	};
+	let ids = (1..=turfs.len())
+		.filter_map(|i| turfs.get(i).ok())
+		.filter(|turf| turf.raw.tag == raw_types::values::ValueTag::Turf)
+		.map(|turf| unsafe { turf.raw.data.id })
+		.collect::<Vec<_>>();
+	let count = GasArena::with_all_mixtures(|all_mixtures| {
+		ids.iter()
+			.filter(|&&id| {
+				turf_gases().contains_key(&id)
+					&& set_turf_air(id, template.as_ref(), all_mixtures).is_ok()
+			})
+			.count()
+	});
+	Ok(Value::from(count as f32))
+}
+
+// Sets a turf's air to the average of its enabled neighbors', to smooth out a pressure jump, and wakes it.
+// Returns how many neighbors went into the average; with none, the air's left alone.
+#[hook("/proc/smooth_turf_air")]
+fn _hook_smooth_turf_air(turf: Value, exclude_immutable_val: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("smooth_turf_air called on a non-turf"));
+	}
+	let exclude_immutable = exclude_immutable_val.as_number().unwrap_or_default() != 0.0;
+	let id = unsafe { turf.raw.data.id };
+	let m = *turf_gases()
+		.get(&id)
+		.ok_or_else(|| runtime!("Attempted to smooth the air of a turf with none!"))?;
+	let (max_x, max_y) = world_dimensions()?;
+	GasArena::with_all_mixtures(|all_mixtures| {
+		let mut average = Mixture::new();
+		let mut count = 0;
+		for (_, adj_id, adj_mix) in m.adjacent_mixes_with_adj_info(all_mixtures, id, max_x, max_y) {
+			if !turf_gases().get(&adj_id).map_or(false, |adj| adj.enabled()) {
+				continue;
+			}
+			let adj_mix = adj_mix.read();
+			if exclude_immutable && adj_mix.is_immutable() {
+				continue;
+			}
+			average.merge(&adj_mix);
+			count += 1;
+		}
+		if count > 0 {
+			average.multiply(1.0 / count as f32);
+			set_turf_air(id, Some(&average), all_mixtures)?;
+		}
+		Ok(Value::from(count as f32))
+	})
+}
+
+// Adds joules straight to a turf's air, or takes them away if negative, but never below TCMB.
+// Immutable air doesn't change, so heating space does nothing.
+#[hook("/proc/heat_turf")]
+fn _hook_heat_turf(turf: Value, joules_val: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("heat_turf called on a non-turf"));
+	}
+	let joules = number(joules_val)?;
+	let id = unsafe { turf.raw.data.id };
+	let mix = turf_gases()
+		.get(&id)
+		.ok_or_else(|| runtime!("Attempted to heat a turf with no air!"))?
+		.mix;
+	let heated = GasArena::with_all_mixtures(|all_mixtures| -> Result<bool, Runtime> {
+		let mut air = all_mixtures
+			.get(mix)
+			.ok_or_else(|| runtime!("No gas mixture with ID {} exists!", mix))?
+			.write();
+		if air.is_immutable() || air.total_moles() <= 0.0 {
+			return Ok(false);
+		}
+		let cap = air.heat_capacity();
+		let new_temp = ((cap * air.get_temperature() + joules) / cap).max(TCMB);
+		air.set_temperature(new_temp);
+		Ok(true)
+	})?;
+	if heated {
+		wake_turf(id);
+	}
+	Ok(Value::from(heated))
+}
+
+#[hook("/proc/set_turf_frozen")]
+fn _hook_set_turf_frozen(turf: Value, frozen_val: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("set_turf_frozen called on a non-turf"));
+	}
+	let frozen = frozen_val.as_number().unwrap_or_default() != 0.0;
+	let mut turf = turf_gases()
+		.get_mut(&unsafe { turf.raw.data.id })
+		.ok_or_else(|| runtime!("Attempted to freeze a turf with no air!"))?;
+	let before = turf.simulation_level;
+	if frozen {
+		turf.simulation_level |= SIMULATION_LEVEL_FROZEN;
+	} else {
+		turf.simulation_level &= !SIMULATION_LEVEL_FROZEN;
+	}
+	set_mix_frozen(turf.mix, frozen);
+	if turf.simulation_level != before {
+		bump_topology_generation();
+	}
+	Ok(Value::null())
+}
+
+/*
+	Walks the zone around the start turf breadth-first, handing visit each turf along with how many steps it is
+	from the start, until visit returns false or limit turfs have been visited. Gas doesn't go through immutable
+	turfs, like space, or ones that aren't simulated, like frozen ones, so neither does this; they're never visited.
+	Sleeping turfs are, since they're still part of the zone.
+*/
+fn walk_zone(
+	all_mixtures: &[parking_lot::RwLock<Mixture>],
+	start: TurfID,
+	max_x: i32,
+	max_y: i32,
+	limit: usize,
+	mut visit: impl FnMut(TurfID, &TurfMixture, usize) -> bool,
+) {
+	let in_zone = |m: &TurfMixture| {
+		m.simulated()
+			&& all_mixtures
+				.get(m.mix)
+				.map_or(false, |air| !air.read().is_immutable())
+	};
+	let mut seen: std::collections::HashSet<TurfID> = std::collections::HashSet::new();
+	let mut queue: std::collections::VecDeque<(TurfID, TurfMixture, usize)> =
+		std::collections::VecDeque::new();
+	match turf_gases().get(&start).map(|m| *m) {
+		Some(m) if in_zone(&m) => {
+			seen.insert(start);
+			queue.push_back((start, m, 0));
+		}
+		_ => return,
+	}
+	let mut visited = 0;
+	while let Some((i, m, dist)) = queue.pop_front() {
+		if visited >= limit || !visit(i, &m, dist) {
+			return;
+		}
+		visited += 1;
+		for (_, loc) in adjacent_tile_ids(m.adjacency, i, max_x, max_y) {
+			if !seen.insert(loc) {
+				continue;
+			}
+			if let Some(adj) = turf_gases().get(&loc).map(|m| *m).filter(in_zone) {
+				queue.push_back((loc, adj, dist + 1));
+			}
+		}
+	}
+}
+
+// Every turf in the zone around the given one, breadth-first, up to the given limit.
+fn flood_fill_zone(
+	start: TurfID,
+	max_x: i32,
+	max_y: i32,
+	limit: usize,
+) -> Vec<(TurfID, TurfMixture)> {
+	let mut zone: Vec<(TurfID, TurfMixture)> = Vec::new();
+	GasArena::with_all_mixtures(|all_mixtures| {
+		walk_zone(all_mixtures, start, max_x, max_y, limit, |i, m, _| {
+			zone.push((i, *m));
+			true
+		})
+	});
+	zone
+}
+
+// Whether the second turf can be reached from the first within the given number of turfs.
+// Doesn't go through immutable turfs, since gas doesn't either, so they're never connected to anything.
+fn turfs_connected(a: TurfID, b: TurfID, max_x: i32, max_y: i32, limit: usize) -> bool {
+	let mut connected = false;
+	GasArena::with_all_mixtures(|all_mixtures| {
+		walk_zone(all_mixtures, a, max_x, max_y, limit, |i, _, _| {
+			connected = i == b;
+			!connected
+		})
+	});
+	connected
+}
+
+/// Gets the air of the turf at the given coordinates, or null if they're off the map or the turf has no air.
+#[hook("/proc/turf_air_at")]
+fn _hook_turf_air_at(x_val: Value, y_val: Value, z_val: Value) {
+	let (x, y, z) = (
+		number(x_val)? as i32,
+		number(y_val)? as i32,
+		number(z_val)? as i32,
+	);
+	let (max_x, max_y) = world_dimensions()?;
+	let max_z = number(&auxtools::Value::world().get(byond_string!("maxz"))?)? as i32;
+	if x < 1 || y < 1 || z < 1 || x > max_x || y > max_y || z > max_z {
+		return Ok(Value::null());
+	}
+	let id = ((x - 1) + (y - 1) * max_x + (z - 1) * max_x * max_y) as TurfID;
+	if !turf_gases().contains_key(&id) {
+		return Ok(Value::null());
+	}
+	unsafe { Value::turf_by_id_unchecked(id) }.get(byond_string!("air"))
+}
+
+#[hook("/proc/turfs_connected")]
+fn _hook_turfs_connected(turf_a: Value, turf_b: Value, limit_val: Value) {
+	if turf_a.raw.tag != raw_types::values::ValueTag::Turf
+		|| turf_b.raw.tag != raw_types::values::ValueTag::Turf
+	{
+		return Err(runtime!("turfs_connected called with a non-turf"));
+	}
+	let limit = number(limit_val)? as usize;
+	let (max_x, max_y) = world_dimensions()?;
+	Ok(Value::from(turfs_connected(
+		unsafe { turf_a.raw.data.id },
+		unsafe { turf_b.raw.data.id },
+		max_x,
+		max_y,
+		limit,
+	)))
+}
+
+/// How many ticks it's been since the turf was last equalized or reacted, or null if it never has been.
+/// Handy for finding turfs that are getting skipped when they shouldn't be.
+#[hook("/proc/turf_last_processed")]
+fn _hook_turf_last_processed(turf: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("turf_last_processed called on a non-turf"));
+	}
+	let tick = PROCESSING_TICK.load(Ordering::Relaxed);
+	Ok(turf_last_processed()
+		.get(&unsafe { turf.raw.data.id })
+		.map_or_else(Value::null, |last| {
+			Value::from(tick.saturating_sub(*last) as f32)
+		}))
+}
+
+/// How many ticks it's been since the turf's air last changed what's in it, or null if that's not being tracked.
+/// Needs SSair's track_air_staleness set; turfs that stay stale for a long time are the ones worth sleeping.
+#[hook("/proc/turf_air_staleness")]
+fn _hook_turf_air_staleness(turf: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("turf_air_staleness called on a non-turf"));
+	}
+	let tick = PROCESSING_TICK.load(Ordering::Relaxed);
+	Ok(AIR_CHANGE_TICKS
+		.read()
+		.as_ref()
+		.and_then(|changes| {
+			changes
+				.get(&unsafe { turf.raw.data.id })
+				.map(|entry| Value::from(tick.saturating_sub(entry.1) as f32))
+		})
+		.unwrap_or_else(Value::null))
+}
+
+/// How many turfs the zone-wide procs go through if they're not given a limit of their own.
+const DEFAULT_ZONE_LIMIT: usize = 10000;
+
+// How many steps each turf in the zone is from the start, breadth first, for up to limit turfs.
+// Immutable turfs aren't part of any zone.
+fn zone_distances(
+	start: TurfID,
+	max_x: i32,
+	max_y: i32,
+	limit: usize,
+) -> std::collections::HashMap<TurfID, usize> {
+	let mut distances: std::collections::HashMap<TurfID, usize> = std::collections::HashMap::new();
+	GasArena::with_all_mixtures(|all_mixtures| {
+		walk_zone(all_mixtures, start, max_x, max_y, limit, |i, _, dist| {
+			distances.insert(i, dist);
+			true
+		})
+	});
+	distances
+}
+
+/// Sets every turf in the zone to a temperature between the two given, by how far it is from each end turf.
+/// An optional fifth argument caps how many turfs that covers. Returns how many turfs were set.
+#[hook("/proc/set_zone_gradient")]
+fn _hook_set_zone_gradient(
+	turf_start: Value,
+	turf_end: Value,
+	temp_start_val: Value,
+	temp_end_val: Value,
+) {
+	if turf_start.raw.tag != raw_types::values::ValueTag::Turf
+		|| turf_end.raw.tag != raw_types::values::ValueTag::Turf
+	{
+		return Err(runtime!("set_zone_gradient called with a non-turf"));
+	}
+	let (temp_start, temp_end) = (number(temp_start_val)?, number(temp_end_val)?);
+	let (max_x, max_y) = world_dimensions()?;
+	let limit = args
+		.get(4)
+		.and_then(|v| v.as_number().ok())
+		.map_or(DEFAULT_ZONE_LIMIT, |n| n.max(0.0) as usize);
+	let from_start = zone_distances(unsafe { turf_start.raw.data.id }, max_x, max_y, limit);
+	let end = unsafe { turf_end.raw.data.id };
+	if !from_start.contains_key(&end) {
+		return Err(runtime!(
+			"set_zone_gradient called with turfs that aren't in the same zone"
+		));
+	}
+	let from_end = zone_distances(end, max_x, max_y, limit);
+	GasArena::with_all_mixtures(|all_mixtures| {
+		for (i, &dist_start) in from_start.iter() {
+			let dist_end = from_end.get(i).copied().unwrap_or_default();
+			let frac = if dist_start + dist_end > 0 {
+				dist_start as f32 / (dist_start + dist_end) as f32
+			} else {
+				0.0
+			};
+			if let Some(m) = turf_gases().get(i) {
+				if let Some(air) = all_mixtures.get(m.mix) {
+					air.write()
+						.set_temperature(temp_start + (temp_end - temp_start) * frac);
+				}
+			}
+		}
+	});
+	for &i in from_start.keys() {
+		wake_turf(i);
+	}
+	Ok(Value::from(from_start.len() as f32))
+}
+
+/// Sets the air of every turf in the zone that isn't frozen to the one temperature, no colder than the cosmic
+/// background, and wakes them all up, sleeping ones included. An optional third argument caps how many turfs
+/// that covers. Returns how many turfs were set.
+#[hook("/proc/set_zone_temperature")]
+fn _hook_set_zone_temperature(turf: Value, temp_val: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("set_zone_temperature called with a non-turf"));
+	}
+	let temperature = number(temp_val)?.max(TCMB);
+	let (max_x, max_y) = world_dimensions()?;
+	let limit = args
+		.get(2)
+		.and_then(|v| v.as_number().ok())
+		.map_or(DEFAULT_ZONE_LIMIT, |n| n.max(0.0) as usize);
+	let zone = zone_distances(unsafe { turf.raw.data.id }, max_x, max_y, limit);
+	let set_turfs = GasArena::with_all_mixtures(|all_mixtures| {
+		zone.keys()
+			.copied()
+			.filter_map(|i| {
+				let m = turf_gases().get(&i).filter(|m| m.simulated())?;
+				all_mixtures
+					.get(m.mix)?
+					.write()
+					.set_temperature(temperature);
+				Some(i)
+			})
+			.collect::<Vec<_>>()
+	});
+	for &i in set_turfs.iter() {
+		wake_turf(i);
+	}
+	Ok(Value::from(set_turfs.len() as f32))
+}
+
+/// Searches outward from the start turf through connected air, returning the closest turf with at least
+/// threshold moles of the gas, or null if there isn't one within max_range steps. The start turf counts;
+/// immutable turfs never do, and the search doesn't go through them.
+#[hook("/proc/nearest_turf_with_gas")]
+fn _hook_nearest_turf_with_gas(
+	start: Value,
+	gas_type: Value,
+	threshold_val: Value,
+	max_range_val: Value,
+) {
+	if start.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("nearest_turf_with_gas called with a non-turf"));
+	}
+	let idx = gas_idx_from_value(gas_type)?;
+	let threshold = number(threshold_val)?;
+	let max_range = number(max_range_val)?.max(0.0) as usize;
+	let (max_x, max_y) = world_dimensions()?;
+	let mut found = None;
+	GasArena::with_all_mixtures(|all_mixtures| {
+		walk_zone(
+			all_mixtures,
+			unsafe { start.raw.data.id },
+			max_x,
+			max_y,
+			usize::MAX,
+			|i, m, dist| {
+				if dist > max_range {
+					return false;
+				}
+				if all_mixtures
+					.get(m.mix)
+					.map_or(false, |air| air.read().get_moles(idx) >= threshold)
+				{
+					found = Some(i);
+				}
+				found.is_none()
+			},
+		)
+	});
+	Ok(found.map_or_else(Value::null, |i| unsafe { Value::turf_by_id_unchecked(i) }))
+}
+
+// Temperature, volume, then each gas id present followed by its moles. Meant to be cheap to send along to clients.
+#[hook("/proc/turf_air_state")]
+fn _hook_turf_air_state(turf: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Ok(Value::null());
+	}
+	let mix = match turf_gases().get(&unsafe { turf.raw.data.id }) {
+		Some(entry) => entry.mix,
+		None => return Ok(Value::null()),
+	};
+	let state = GasArena::with_all_mixtures(|all_mixtures| {
+		all_mixtures.get(mix).map(|lock| {
+			let air = lock.read();
+			let gases = air
+				.enumerate()
+				.filter(|&(_, amt)| amt > 0.0)
+				.collect::<Vec<_>>();
+			(air.get_temperature(), air.volume, gases)
+		})
+	});
+	if let Some((temperature, volume, gases)) = state {
+		let ret = List::new();
+		ret.append(Value::from(temperature));
+		ret.append(Value::from(volume));
+		for (idx, amt) in gases {
+			ret.append(Value::from_string(&*gas_idx_to_id(idx)?)?);
+			ret.append(Value::from(amt));
+		}
+		Ok(Value::from(ret))
+	} else {
+		Ok(Value::null())
+	}
+}
+
+/// Every turf in the air graph whose pressure is over the threshold, scaled the same way as return_pressure.
+/// Immutable turfs are left out.
+#[hook("/proc/turfs_over_pressure")]
+fn _hook_turfs_over_pressure(threshold_val: Value) {
+	let threshold = number(threshold_val)? / crate::pressure_scale();
+	let mut over = GasArena::with_all_mixtures(|all_mixtures| {
+		turf_gases()
+			.shards()
+			.par_iter()
+			.flat_map_iter(|shard| {
+				shard
+					.read()
+					.iter()
+					.filter(|(_, m_v)| {
+						all_mixtures.get(m_v.get().mix).map_or(false, |lock| {
+							let air = lock.read();
+							!air.is_immutable() && air.return_pressure() > threshold
+						})
+					})
+					.map(|(&i, _)| i)
+					.collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>()
+	});
+	over.sort_unstable();
+	let ret = List::new();
+	for i in over {
+		ret.append(unsafe { Value::turf_by_id_unchecked(i) });
+	}
+	Ok(Value::from(ret))
+}
+
+#[hook("/proc/zone_temperature_extremes")]
+fn _hook_zone_temperature_extremes(turf: Value, limit_val: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("zone_temperature_extremes called on a non-turf"));
+	}
+	let limit = number(limit_val)? as usize;
+	let (max_x, max_y) = world_dimensions()?;
+	let zone = flood_fill_zone(unsafe { turf.raw.data.id }, max_x, max_y, limit);
+	let extremes = GasArena::with_all_mixtures(|all_mixtures| {
+		let mut extremes: Option<((TurfID, f32), (TurfID, f32))> = None;
+		for &(i, m) in &zone {
+			let temp = match all_mixtures.get(m.mix) {
+				Some(mix) => mix.read().get_temperature(),
+				None => continue,
+			};
+			let (min, max) = extremes.get_or_insert(((i, temp), (i, temp)));
+			if temp < min.1 {
+				*min = (i, temp);
+			}
+			if temp > max.1 {
+				*max = (i, temp);
+			}
+		}
+		extremes
+	});
+	let ret = List::new();
+	if let Some(((min_turf, min_temp), (max_turf, max_temp))) = extremes {
+		ret.set(byond_string!("min_temperature"), min_temp)?;
+		ret.set(byond_string!("min_turf"), min_turf as f32)?;
+		ret.set(byond_string!("max_temperature"), max_temp)?;
+		ret.set(byond_string!("max_turf"), max_turf as f32)?;
+	}
+	Ok(Value::from(ret))
+}
+
+// Points turfs at wherever their mixes got moved to.
+fn remap_turf_mixes(remap: &std::collections::HashMap<usize, usize, FxBuildHasher>) {
+	let moved = |mix: usize| remap.get(&mix).copied().unwrap_or(mix);
+	for mut turf in turf_gases().iter_mut() {
+		turf.mix = moved(turf.mix);
+		for adj in turf.adjacents.iter_mut() {
+			*adj = adj.and_then(|adj_mix| nonmax::NonMaxUsize::new(moved(adj_mix.get())));
+		}
+	}
+	let frozen = frozen_mixes()
+		.iter()
+		.map(|mix| *mix.key())
+		.collect::<Vec<_>>();
+	frozen_mixes().clear();
+	for mix in frozen {
+		frozen_mixes().insert(moved(mix));
+	}
+	bump_topology_generation();
+}
+
+#[hook("/datum/controller/subsystem/air/proc/defragment_gas_mixtures")]
+fn _hook_defragment_gas_mixtures() {
+	if processing::thread_running() || processing::heat_running() {
+		return Err(runtime!(
+			"Attempted to defragment gas mixtures while atmos is processing!"
+		));
+	}
+	// queued callbacks, like planet equalization and explosive decompression, hold onto the mix indices they were made with
+	if !byond_callback_sender().is_empty() {
+		return Err(runtime!(
+			"Attempted to defragment gas mixtures with atmos callbacks still queued!"
+		));
+	}
+	let remap = GasArena::defragment();
+	remap_turf_mixes(&remap);
+	Ok(Value::from(remap.len() as f32))
+}
+
 #[hook("/turf/proc/__update_auxtools_turf_adjacency_info")]
 fn _hook_adjacent_turfs() {
 	if let Ok(adjacent_list) = src.get_list(byond_string!("atmos_adjacent_turfs")) {
@@ -358,6 +1200,7 @@ fn _hook_adjacent_turfs() {
 				turf.adjacents = [None; 6];
 			});
 	}
+	bump_topology_generation();
 	if let Ok(atmos_blocked_directions) =
 		src.get_number(byond_string!("conductivity_blocked_directions"))
 	{
@@ -412,10 +1255,10 @@ fn _hook_set_temperature() {
 	turf_temperatures()
 		.entry(unsafe { src.raw.data.id })
 		.and_modify(|turf| {
-			turf.temperature = argument;
+			turf.temperature = argument.max(TCMB);
 		})
 		.or_insert_with(|| ThermalInfo {
-			temperature: argument,
+			temperature: argument.max(TCMB),
 			thermal_conductivity: src
 				.get_number(byond_string!("thermal_conductivity"))
 				.unwrap(),
@@ -488,6 +1331,8 @@ pub const SIMULATION_LEVEL_DIFFUSE: u8 = 1;
 pub const SIMULATION_LEVEL_ALL: u8 = 2;
 pub const SIMULATION_LEVEL_ANY: u8 = SIMULATION_LEVEL_DIFFUSE | SIMULATION_LEVEL_ALL;
 pub const SIMULATION_LEVEL_DISABLED: u8 = 4;
+// Set by admins and the like; frozen turfs are left completely alone by processing.
+pub const SIMULATION_LEVEL_FROZEN: u8 = 8;
 
 fn adjacent_tile_id(id: u8, i: TurfID, max_x: i32, max_y: i32) -> TurfID {
 	let z_size = max_x * max_y;
@@ -548,3 +1393,29 @@ fn adjacent_tile_ids(adj: u8, i: TurfID, max_x: i32, max_y: i32) -> AdjacentTile
 		count: 0,
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_simulated() {
+		let awake = TurfMixture {
+			simulation_level: SIMULATION_LEVEL_ALL,
+			..Default::default()
+		};
+		let sleeping = TurfMixture {
+			simulation_level: SIMULATION_LEVEL_ALL | SIMULATION_LEVEL_DISABLED,
+			..Default::default()
+		};
+		let frozen = TurfMixture {
+			simulation_level: SIMULATION_LEVEL_ALL | SIMULATION_LEVEL_FROZEN,
+			..Default::default()
+		};
+		assert!(awake.enabled() && awake.simulated());
+		// sleeping turfs still get zone-wide changes, they just need waking up after
+		assert!(!sleeping.enabled() && sleeping.simulated());
+		assert!(!frozen.simulated());
+		assert!(!TurfMixture::default().simulated());
+	}
+}
@@ -8,6 +8,12 @@ use auxcallback::byond_callback_sender;
 
 use std::cell::Cell;
 
+use std::time::Instant;
+
+use parking_lot::RwLock;
+
+use crate::gas::with_diffusion_coefficients;
+
 type TransferInfo = [f32; 7];
 
 type MixWithID = (TurfID, TurfMixture);
@@ -24,6 +30,282 @@ struct MonstermosInfo {
 
 const OPP_DIR_INDEX: [usize; 7] = [1, 0, 3, 2, 5, 4, 6];
 
+// The turf debug_equalize_graph is watching, and the transfer plan of the last zone it was equalized in, if any yet.
+static DEBUG_EQ_GRAPH: RwLock<Option<(TurfID, Option<Vec<(TurfID, TurfID, f32)>>)>> =
+	parking_lot::const_rwlock(None);
+
+// The biggest transfer out of each turf during the last equalization, as (direction index, moles).
+static TURF_GAS_FLOW: RwLock<Option<HashMap<TurfID, (u8, f32)>>> = parking_lot::const_rwlock(None);
+
+// If set, equalization adds up how much of each gas it moves between turfs; off by default, since it's per transfer.
+static TRACK_EQ_GAS_FLOW: AtomicBool = AtomicBool::new(false);
+
+// Moles of each gas moved between turfs in the last equalization, by gas index, if that's being tracked.
+static EQ_GAS_FLOW: RwLock<Option<Vec<f64>>> = parking_lot::const_rwlock(None);
+
+// The net horizontal push on each turf from the last equalization, as moles moved east and north.
+static TURF_WIND: RwLock<Option<HashMap<TurfID, (f32, f32)>>> = parking_lot::const_rwlock(None);
+
+// East and north components of each direction index; up and down don't blow anything sideways.
+const DIR_VECTORS: [(f32, f32); 7] = [
+	(0.0, 1.0),
+	(0.0, -1.0),
+	(1.0, 0.0),
+	(-1.0, 0.0),
+	(0.0, 0.0),
+	(0.0, 0.0),
+	(0.0, 0.0),
+];
+
+// Which way gas last went across each edge, keyed by the lower turf ID first, for catching gas that sloshes back and forth.
+static EDGE_HISTORY: RwLock<Option<HashMap<(TurfID, TurfID), EdgeHistory>>> =
+	parking_lot::const_rwlock(None);
+
+// How far each turf was from its zone's average moles the last couple of times it was equalized,
+// as (equalization tick, delta then, delta the tick before if it was equalized then too).
+static ZONE_DELTAS: RwLock<Option<HashMap<TurfID, (u64, f32, Option<f32>)>>> =
+	parking_lot::const_rwlock(None);
+
+// Counts equalizations, so edge histories can tell whether they're from the last one.
+static EQUALIZE_TICK: AtomicU64 = AtomicU64::new(0);
+
+static OSCILLATIONS_DETECTED: AtomicU64 = AtomicU64::new(0);
+
+// High pressure turfs the last equalization never got to, because it hit the hard turf limit first.
+static DEFERRED_TURFS: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn deferred_equalize_turfs() -> usize {
+	DEFERRED_TURFS.load(Ordering::Relaxed) as usize
+}
+
+// How much the mutable mixes equalization moved gas between gained or lost in total, and how much it moved,
+// since it was last taken. Should stay at zero, give or take float error.
+#[cfg(debug_assertions)]
+static EQ_MOLE_BALANCE: RwLock<(f64, f64)> = parking_lot::const_rwlock((0.0, 0.0));
+
+#[cfg(debug_assertions)]
+pub(crate) fn take_equalize_mole_balance() -> (f64, f64) {
+	std::mem::take(&mut *EQ_MOLE_BALANCE.write())
+}
+
+// Moles that count toward the balance; gas going into or out of immutable mixes is meant to appear or vanish.
+#[cfg(debug_assertions)]
+fn balanced_moles(mix: &Mixture) -> f64 {
+	if mix.is_immutable() {
+		0.0
+	} else {
+		mix.total_moles() as f64
+	}
+}
+
+// How many times in a row an edge has to reverse before it counts as oscillating.
+const OSCILLATION_FLIPS: u8 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct EdgeHistory {
+	forward: bool,
+	flips: u8,
+	tick: u64,
+}
+
+impl EdgeHistory {
+	// What the history becomes when gas moves across the edge this tick; flips only count if they're tick after tick.
+	fn next(prev: Option<Self>, forward: bool, tick: u64) -> Self {
+		let flips = match prev {
+			Some(prev) if prev.tick == tick => prev.flips,
+			Some(prev) if prev.tick + 1 == tick && prev.forward != forward => {
+				prev.flips.saturating_add(1)
+			}
+			_ => 0,
+		};
+		EdgeHistory {
+			forward,
+			flips,
+			tick,
+		}
+	}
+	fn oscillating(&self) -> bool {
+		self.flips >= OSCILLATION_FLIPS
+	}
+}
+
+// Zones only change when the map does, so they're kept around until the topology generation moves on.
+static ZONE_CACHE: RwLock<Option<ZoneCache>> = parking_lot::const_rwlock(None);
+
+#[shutdown]
+fn _shutdown_turf_gas_flow() {
+	*TURF_GAS_FLOW.write() = None;
+	*TURF_WIND.write() = None;
+	*EQ_GAS_FLOW.write() = None;
+	*ZONE_CACHE.write() = None;
+	*EDGE_HISTORY.write() = None;
+	*ZONE_DELTAS.write() = None;
+	*DEBUG_EQ_GRAPH.write() = None;
+	OSCILLATIONS_DETECTED.store(0, Ordering::Relaxed);
+}
+
+/*
+	Returns the plan monstermos made for the zone this turf was last equalized in, before any of it was carried out,
+	as a flat list of from turf, to turf, moles for every edge moving anything. Only one turf is watched at a time,
+	so the first call for a turf starts watching it and returns null; the plan shows up once it's next equalized.
+	Transfers to and from planetary atmos aren't included.
+*/
+#[hook("/proc/debug_equalize_graph")]
+fn _hook_debug_equalize_graph(turf: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("debug_equalize_graph called on a non-turf"));
+	}
+	let id = unsafe { turf.raw.data.id };
+	let mut debug_graph = DEBUG_EQ_GRAPH.write();
+	match &*debug_graph {
+		Some((watched, Some(edges))) if *watched == id => {
+			let ret = List::new();
+			for &(from, to, amount) in edges {
+				ret.append(unsafe { Value::turf_by_id_unchecked(from) });
+				ret.append(unsafe { Value::turf_by_id_unchecked(to) });
+				ret.append(Value::from(amount));
+			}
+			Ok(Value::from(ret))
+		}
+		Some((watched, None)) if *watched == id => Ok(Value::null()),
+		_ => {
+			*debug_graph = Some((id, None));
+			Ok(Value::null())
+		}
+	}
+}
+
+#[hook("/datum/controller/subsystem/air/proc/get_pressure_oscillations")]
+fn _hook_pressure_oscillations() {
+	Ok(Value::from(
+		OSCILLATIONS_DETECTED.load(Ordering::Relaxed) as f32
+	))
+}
+
+#[hook("/proc/turf_gas_flow")]
+fn _hook_turf_gas_flow(turf: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("turf_gas_flow called on a non-turf"));
+	}
+	let id = unsafe { turf.raw.data.id };
+	let (dir, moles) = TURF_GAS_FLOW
+		.read()
+		.as_ref()
+		.and_then(|flows| flows.get(&id).copied())
+		.map_or((0.0, 0.0), |(j, moles)| ((1 << j) as f32, moles));
+	let ret = List::new();
+	ret.append(Value::from(dir));
+	ret.append(Value::from(moles));
+	Ok(Value::from(ret))
+}
+
+// Turfs within this many moles of their zone's average count as equalized.
+const EQUILIBRIUM_MOLES: f32 = MINIMUM_MOLES_DELTA_TO_MOVE;
+
+/*
+	Guesses how many more ticks it'll take a turf to reach its zone's average, assuming it keeps closing the gap
+	as fast as it did over the last tick. -1 if there's nothing to go on, or it didn't get any closer.
+*/
+fn ticks_to_equilibrium(prev_delta: Option<f32>, cur_delta: f32) -> f32 {
+	let remaining = cur_delta.abs();
+	if remaining <= EQUILIBRIUM_MOLES {
+		return 0.0;
+	}
+	match prev_delta.map(|prev| prev.abs() - remaining) {
+		Some(closed) if closed > 0.0 => (remaining / closed).ceil(),
+		_ => -1.0,
+	}
+}
+
+/*
+	Estimates how many ticks until the turf's air matches the rest of its zone, going by how much closer
+	it got over the last tick. 0 if it wasn't part of any zone last equalization, which means it's already even
+	with its surroundings; -1 if it's not being processed at all, or there's no progress to extrapolate from yet.
+*/
+#[hook("/proc/turf_time_to_equilibrium")]
+fn _hook_turf_time_to_equilibrium(turf: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("turf_time_to_equilibrium called on a non-turf"));
+	}
+	let id = unsafe { turf.raw.data.id };
+	if !turf_gases().get(&id).map_or(false, |m| m.enabled()) {
+		return Ok(Value::from(-1.0));
+	}
+	let tick = EQUALIZE_TICK.load(Ordering::Relaxed);
+	Ok(Value::from(
+		ZONE_DELTAS
+			.read()
+			.as_ref()
+			.and_then(|deltas| deltas.get(&id).copied())
+			.filter(|&(last_tick, _, _)| last_tick == tick)
+			.map_or(0.0, |(_, delta, prev_delta)| {
+				ticks_to_equilibrium(prev_delta, delta)
+			}),
+	))
+}
+
+/// Turns tracking how much of each gas equalization moves on or off.
+#[hook("/proc/set_equalize_gas_flow_tracking")]
+fn _hook_set_equalize_gas_flow_tracking(enabled: Value) {
+	TRACK_EQ_GAS_FLOW.store(
+		enabled.as_number().unwrap_or_default() != 0.0,
+		Ordering::Relaxed,
+	);
+	Ok(Value::null())
+}
+
+/// How many moles of each gas the last equalization moved between turfs, as a list of gas id to moles.
+/// Only gases that moved at all are in it, and it's empty unless tracking's been turned on.
+/// Gas coming from or going to planetary atmos isn't counted.
+#[hook("/proc/equalize_gas_flow_breakdown")]
+fn _hook_equalize_gas_flow_breakdown() {
+	let ret = List::new();
+	if let Some(totals) = EQ_GAS_FLOW.read().as_ref() {
+		for (idx, &moles) in totals.iter().enumerate() {
+			if moles > 0.0 {
+				ret.set(
+					Value::from_string(&*gas_idx_to_id(idx)?)?,
+					Value::from(moles as f32),
+				)?;
+			}
+		}
+	}
+	Ok(Value::from(ret))
+}
+
+// The byond dir, diagonals included, closest to the way the vector points; 0 for no vector at all.
+fn wind_dir(east: f32, north: f32) -> u8 {
+	// east, northeast, north, northwest, west, southwest, south, southeast
+	const DIRS: [u8; 8] = [4, 5, 1, 9, 8, 10, 2, 6];
+	if east == 0.0 && north == 0.0 {
+		return 0;
+	}
+	let octant = (north.atan2(east) / std::f32::consts::FRAC_PI_4).round() as i32;
+	DIRS[octant.rem_euclid(8) as usize]
+}
+
+/*
+	Which way the last equalization pushed gas through the turf and how hard, as a list of byond dir then moles.
+	Gas leaving and gas arriving both push the same way, so a turf gas is passing through counts both.
+	Ups and downs are left out. A turf in equilibrium with its neighbors gets 0 and 0.
+*/
+#[hook("/proc/turf_wind_vector")]
+fn _hook_turf_wind_vector(turf: Value) {
+	if turf.raw.tag != raw_types::values::ValueTag::Turf {
+		return Err(runtime!("turf_wind_vector called on a non-turf"));
+	}
+	let id = unsafe { turf.raw.data.id };
+	let (east, north) = TURF_WIND
+		.read()
+		.as_ref()
+		.and_then(|winds| winds.get(&id).copied())
+		.unwrap_or((0.0, 0.0));
+	let ret = List::new();
+	ret.append(Value::from(wind_dir(east, north) as f32));
+	ret.append(Value::from(east.hypot(north)));
+	Ok(Value::from(ret))
+}
+
 impl MonstermosInfo {
 	fn adjust_eq_movement(&mut self, adjacent: &mut Self, dir_index: usize, amount: f32) {
 		self.transfer_dirs[dir_index] += amount;
@@ -33,6 +315,27 @@ impl MonstermosInfo {
 	}
 }
 
+/*
+	Dense gases can be made to act like walls for equalization: a turf with at least the threshold
+	fraction of its moles in the blocking category is left out of zones entirely, so no gas gets
+	routed into, out of or through it. It still shares with its neighbors in the regular processing
+	pass, so the blocking gas itself spreads out (and stops blocking once it's thin enough).
+*/
+fn blocks_equalization(mix: &Mixture, (category, threshold): (u32, f32)) -> bool {
+	let total_moles = mix.total_moles();
+	total_moles > 0.0 && mix.category_moles(category) >= total_moles * threshold
+}
+
+fn turf_blocks_equalization(m: &TurfMixture, blocking_gas: Option<(u32, f32)>) -> bool {
+	blocking_gas.map_or(false, |blocking| {
+		GasArena::with_all_mixtures(|all_mixtures| {
+			all_mixtures
+				.get(m.mix)
+				.map_or(false, |mix| blocks_equalization(&mix.read(), blocking))
+		})
+	})
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -45,6 +348,253 @@ mod tests {
 		assert_eq!(info_a.transfer_dirs[1], 5.0);
 		assert_eq!(info_b.transfer_dirs[0], -5.0);
 	}
+	#[test]
+	fn test_record_transfer() {
+		let mut txn = EqTransaction::new(0.0, false);
+		txn.record_transfer(1, 2, 0, 5.0);
+		txn.record_transfer(1, 3, 2, 8.0);
+		assert!(txn.pressure_diffs.contains(&(2, 1, -5.0)));
+		assert!(txn.pressure_diffs.contains(&(3, 1, -8.0)));
+		let net: f32 = txn.pressure_diffs.iter().map(|&(_, _, amt)| amt).sum();
+		assert_eq!(net, 0.0);
+		assert_eq!(txn.flows.get(&1), Some(&(2, 8.0)));
+		// 5 north and 8 east out of turf 1
+		assert_eq!(txn.winds.get(&1), Some(&(8.0, 5.0)));
+		assert_eq!(txn.winds.get(&3), Some(&(8.0, 0.0)));
+	}
+	#[test]
+	fn test_gases_moved() {
+		crate::gas::init_test_gases();
+		let mut txn = EqTransaction::new(0.0, false);
+		let mut moved = Mixture::new();
+		moved.set_moles(0, 2.0);
+		moved.set_moles(3, 0.5);
+		// nothing's kept unless tracking's on
+		txn.note_gases_moved(&moved);
+		assert_eq!(txn.gas_moved, None);
+		txn.gas_moved = Some(Vec::new());
+		txn.note_gases_moved(&moved);
+		txn.note_gases_moved(&moved);
+		assert_eq!(txn.gas_moved, Some(vec![4.0, 0.0, 0.0, 1.0]));
+	}
+	#[test]
+	fn test_wind_dir() {
+		assert_eq!(wind_dir(0.0, 0.0), 0);
+		assert_eq!(wind_dir(0.0, 3.0), 1);
+		assert_eq!(wind_dir(0.0, -3.0), 2);
+		assert_eq!(wind_dir(3.0, 0.0), 4);
+		assert_eq!(wind_dir(-3.0, 0.0), 8);
+		assert_eq!(wind_dir(8.0, 5.0), 5);
+		assert_eq!(wind_dir(-8.0, -0.5), 8);
+		assert_eq!(wind_dir(2.0, -2.0), 6);
+	}
+	#[test]
+	fn test_fast_process_full_adjacency() {
+		// Center of a 3x3x3 block, so every one of the six directions has a turf behind it.
+		let center: TurfID = 13;
+		let m = TurfMixture {
+			adjacency: 0b111111,
+			..Default::default()
+		};
+		let mut info: HashMap<TurfID, Cell<MonstermosInfo>> = HashMap::new();
+		info.insert(
+			center,
+			Cell::new(MonstermosInfo {
+				mole_delta: 60.0,
+				..Default::default()
+			}),
+		);
+		for (_, loc) in adjacent_tile_ids(m.adjacency, center, 3, 3) {
+			info.insert(loc, Cell::new(Default::default()));
+		}
+		assert_eq!(info.len(), 7);
+		// One neighbor has already been handled, so only the other five split the delta.
+		let done = adjacent_tile_id(4, center, 3, 3);
+		let mut done_info = info[&done].get();
+		done_info.fast_done = true;
+		info[&done].set(done_info);
+		monstermos_fast_process(center, m, 3, 3, &mut info);
+		let cur = info[&center].get();
+		assert!(cur.fast_done);
+		assert!(cur.mole_delta.abs() < 1e-4);
+		for (j, loc) in adjacent_tile_ids(m.adjacency, center, 3, 3) {
+			let adj = info[&loc].get();
+			let expected = if loc == done { 0.0 } else { 12.0 };
+			assert!((adj.mole_delta - expected).abs() < 1e-4);
+			assert!((cur.transfer_dirs[j as usize] - expected).abs() < 1e-4);
+			assert!((adj.transfer_dirs[OPP_DIR_INDEX[j as usize]] + expected).abs() < 1e-4);
+		}
+	}
+	#[test]
+	fn test_ticks_to_equilibrium() {
+		assert_eq!(ticks_to_equilibrium(None, 0.0), 0.0);
+		assert_eq!(ticks_to_equilibrium(Some(50.0), 0.05), 0.0);
+		assert_eq!(ticks_to_equilibrium(None, 30.0), -1.0);
+		// closed 20 moles of the gap last tick, 30 to go
+		assert_eq!(ticks_to_equilibrium(Some(50.0), 30.0), 2.0);
+		assert_eq!(ticks_to_equilibrium(Some(-50.0), -30.0), 2.0);
+		assert_eq!(ticks_to_equilibrium(Some(30.0), 30.0), -1.0);
+		assert_eq!(ticks_to_equilibrium(Some(20.0), 30.0), -1.0);
+	}
+	#[test]
+	fn test_blocks_equalization() {
+		crate::gas::init_test_gases();
+		let mut mix = Mixture::new();
+		assert!(!blocks_equalization(&mix, (4, 0.5)));
+		mix.set_moles(1, 60.0);
+		mix.set_moles(3, 40.0);
+		assert!(!blocks_equalization(&mix, (4, 0.5)));
+		mix.set_moles(3, 60.0);
+		assert!(blocks_equalization(&mix, (4, 0.5)));
+		assert!(!blocks_equalization(&mix, (1, 0.5)));
+	}
+	#[test]
+	fn test_edge_oscillation() {
+		let mut edge = EdgeHistory::next(None, true, 1);
+		for tick in 2..=4 {
+			assert!(!edge.oscillating());
+			edge = EdgeHistory::next(Some(edge), tick % 2 == 1, tick);
+		}
+		assert!(edge.oscillating());
+		// flowing the same way twice isn't oscillating, nor is a reversal after the edge went quiet for a tick
+		assert_eq!(EdgeHistory::next(Some(edge), edge.forward, 5).flips, 0);
+		assert_eq!(EdgeHistory::next(Some(edge), !edge.forward, 6).flips, 0);
+	}
+}
+
+/*
+	Finalizing a zone is all-or-nothing. Before any gas moves, zone_can_finalize checks that
+	everything finalization could reach is there, so it can't bail partway and leave half the
+	gas moved. Pressure differences and the rest are buffered here and only sent along once the
+	whole zone has gone through.
+*/
+struct EqTransaction {
+	pressure_diffs: Vec<(TurfID, TurfID, f32)>,
+	flows: HashMap<TurfID, (u8, f32)>,
+	winds: HashMap<TurfID, (f32, f32)>,
+	// Moles of each gas moved, if equalization's keeping track.
+	gas_moved: Option<Vec<f64>>,
+	// Edges moving this many moles or fewer are skipped entirely, so we don't spam byond with dust.
+	min_transfer: f32,
+	// If set, edges that keep reversing direction only move half as much; see damp_oscillation.
+	damp_oscillations: bool,
+	edges: HashMap<(TurfID, TurfID), EdgeHistory>,
+	#[cfg(debug_assertions)]
+	mole_balance: (f64, f64),
+}
+
+impl EqTransaction {
+	fn new(min_transfer: f32, damp_oscillations: bool) -> Self {
+		EqTransaction {
+			pressure_diffs: Vec::new(),
+			flows: HashMap::new(),
+			winds: HashMap::new(),
+			gas_moved: TRACK_EQ_GAS_FLOW.load(Ordering::Relaxed).then(Vec::new),
+			min_transfer,
+			damp_oscillations,
+			edges: HashMap::new(),
+			#[cfg(debug_assertions)]
+			mole_balance: (0.0, 0.0),
+		}
+	}
+	// Some geometry makes gas ping-pong across an edge every tick; halving the transfer lets it settle in the middle instead.
+	fn damp_oscillation(&mut self, from: TurfID, to: TurfID, amount: f32) -> f32 {
+		if !self.damp_oscillations {
+			return amount;
+		}
+		let key = (from.min(to), from.max(to));
+		let prev = EDGE_HISTORY
+			.read()
+			.as_ref()
+			.and_then(|history| history.get(&key).copied());
+		let next = EdgeHistory::next(prev, from < to, EQUALIZE_TICK.load(Ordering::Relaxed));
+		self.edges.insert(key, next);
+		if next.oscillating() {
+			amount * 0.5
+		} else {
+			amount
+		}
+	}
+	// Both sides see the transfer: the giver as positive, the receiver as an equal and opposite inflow.
+	fn record_transfer(&mut self, from: TurfID, to: TurfID, dir: u8, amount: f32) {
+		self.pressure_diffs.push((from, to, amount));
+		self.pressure_diffs.push((to, from, -amount));
+		let flow = self.flows.entry(from).or_insert((dir, 0.0));
+		if amount > flow.1 {
+			*flow = (dir, amount);
+		}
+		let (east, north) = DIR_VECTORS[dir as usize];
+		for turf in [from, to].iter() {
+			let wind = self.winds.entry(*turf).or_default();
+			wind.0 += east * amount;
+			wind.1 += north * amount;
+		}
+	}
+	fn note_gases_moved(&mut self, moved: &Mixture) {
+		if let Some(totals) = self.gas_moved.as_mut() {
+			for (idx, amt) in moved.enumerate() {
+				if totals.len() <= idx {
+					totals.resize(idx + 1, 0.0);
+				}
+				totals[idx] += amt as f64;
+			}
+		}
+	}
+	fn commit(self) {
+		#[cfg(debug_assertions)]
+		{
+			let mut balance = EQ_MOLE_BALANCE.write();
+			balance.0 += self.mole_balance.0;
+			balance.1 += self.mole_balance.1;
+		}
+		if !self.edges.is_empty() {
+			let oscillating = self.edges.values().filter(|e| e.oscillating()).count();
+			OSCILLATIONS_DETECTED.fetch_add(oscillating as u64, Ordering::Relaxed);
+			EDGE_HISTORY
+				.write()
+				.get_or_insert_with(HashMap::new)
+				.extend(self.edges);
+		}
+		if !self.flows.is_empty() {
+			TURF_GAS_FLOW
+				.write()
+				.get_or_insert_with(HashMap::new)
+				.extend(self.flows);
+		}
+		if !self.winds.is_empty() {
+			TURF_WIND
+				.write()
+				.get_or_insert_with(HashMap::new)
+				.extend(self.winds);
+		}
+		if let Some(moved) = self.gas_moved {
+			let mut flow = EQ_GAS_FLOW.write();
+			let totals = flow.get_or_insert_with(Vec::new);
+			if totals.len() < moved.len() {
+				totals.resize(moved.len(), 0.0);
+			}
+			for (total, amt) in totals.iter_mut().zip(moved) {
+				*total += amt;
+			}
+		}
+		let pressure_diffs = self.pressure_diffs;
+		if pressure_diffs.is_empty() {
+			return;
+		}
+		let _ = byond_callback_sender().send(Box::new(move || {
+			for &(i, adj_id, amount) in &pressure_diffs {
+				let real_amount = Value::from(-amount);
+				let turf = unsafe { Value::turf_by_id_unchecked(i as u32) };
+				let other_turf = unsafe { Value::turf_by_id_unchecked(adj_id as u32) };
+				if let Err(e) =
+					turf.call("consider_pressure_difference", &[&other_turf, &real_amount])
+				{
+					turf.call("stack_trace", &[&Value::from_string(e.message.as_str())?])?;
+				}
+			}
+			Ok(Value::null())
+		}));
+	}
 }
 
 fn finalize_eq(
@@ -53,10 +603,10 @@ fn finalize_eq(
 	info: &HashMap<TurfID, Cell<MonstermosInfo>>,
 	max_x: i32,
 	max_y: i32,
-) {
-	let sender = byond_callback_sender();
+	txn: &mut EqTransaction,
+) -> Option<()> {
 	let transfer_dirs = {
-		let monstermos_orig = info.get(&i).unwrap();
+		let monstermos_orig = info.get(&i)?;
 		let mut monstermos_copy = monstermos_orig.get();
 		let transfer_dirs = monstermos_copy.transfer_dirs;
 		monstermos_copy
@@ -69,15 +619,15 @@ fn finalize_eq(
 	let planet_transfer_amount = transfer_dirs[6];
 	if planet_transfer_amount > 0.0 {
 		if turf.total_moles() < planet_transfer_amount {
-			finalize_eq_neighbors(i, turf, transfer_dirs, info, max_x, max_y);
+			finalize_eq_neighbors(i, turf, transfer_dirs, info, max_x, max_y, txn)?;
 		}
 		GasArena::with_all_mixtures(|all_mixtures| {
 			all_mixtures
-				.get(turf.mix)
-				.unwrap()
+				.get(turf.mix)?
 				.write()
 				.remove(planet_transfer_amount);
-		})
+			Some(())
+		})?;
 	} else if planet_transfer_amount < 0.0 {
 		if let Some(air_entry) = turf.planetary_atmos.and_then(|i| planetary_atmos().get(&i)) {
 			let planet_air = air_entry.value();
@@ -85,19 +635,20 @@ fn finalize_eq(
 			if planet_sum > 0.0 {
 				GasArena::with_all_mixtures(|all_mixtures| {
 					all_mixtures
-						.get(turf.mix)
-						.unwrap()
+						.get(turf.mix)?
 						.write()
 						.merge(&(planet_air * (-planet_transfer_amount / planet_sum)));
-				});
+					Some(())
+				})?;
 			}
 		}
 	}
 	for (j, adj_id) in adjacent_tile_ids(turf.adjacency, i, max_x, max_y) {
 		let amount = transfer_dirs[j as usize];
-		if amount > 0.0 {
+		if amount > txn.min_transfer {
+			let amount = txn.damp_oscillation(i, adj_id, amount);
 			if turf.total_moles() < amount {
-				finalize_eq_neighbors(i, turf, transfer_dirs, info, max_x, max_y);
+				finalize_eq_neighbors(i, turf, transfer_dirs, info, max_x, max_y, txn)?;
 			}
 			if let Some(adj_orig) = info.get(&adj_id) {
 				if let Some(adj_turf) = turf_gases().get(&adj_id) {
@@ -105,35 +656,38 @@ fn finalize_eq(
 					adj_info.transfer_dirs[OPP_DIR_INDEX[j as usize]] = 0.0;
 					if turf.mix != adj_turf.mix {
 						GasArena::with_all_mixtures(|all_mixtures| {
-							let our_entry = all_mixtures.get(turf.mix).unwrap();
-							let their_entry = all_mixtures.get(adj_turf.mix).unwrap();
+							let our_entry = all_mixtures.get(turf.mix)?;
+							let their_entry = all_mixtures.get(adj_turf.mix)?;
 							let mut air = our_entry.write();
 							let mut other_air = their_entry.write();
-							other_air.merge(&air.remove(amount));
-						});
-					}
-					adj_orig.set(adj_info);
-					sender
-						.send(Box::new(move || {
-							let real_amount = Value::from(-amount);
-							let turf = unsafe { Value::turf_by_id_unchecked(i as u32) };
-							let other_turf = unsafe { Value::turf_by_id_unchecked(adj_id as u32) };
-							if let Err(e) = turf
-								.call("consider_pressure_difference", &[&other_turf, &real_amount])
+							#[cfg(debug_assertions)]
+							let moles_before = balanced_moles(&air) + balanced_moles(&other_air);
+							let moved = if cfg!(feature = "monstermos_diffusion") {
+								with_diffusion_coefficients(|coefficients| {
+									air.remove_by_diffusion(amount, coefficients)
+								})
+							} else {
+								air.remove(amount)
+							};
+							other_air.merge(&moved);
+							#[cfg(debug_assertions)]
 							{
-								turf.call(
-									"stack_trace",
-									&[&Value::from_string(e.message.as_str())?],
-								)
-								.unwrap();
+								txn.mole_balance.0 += balanced_moles(&air)
+									+ balanced_moles(&other_air)
+									- moles_before;
+								txn.mole_balance.1 += moved.total_moles() as f64;
 							}
-							Ok(Value::null())
-						}))
-						.unwrap();
+							txn.note_gases_moved(&moved);
+							Some(())
+						})?;
+					}
+					adj_orig.set(adj_info);
+					txn.record_transfer(i, adj_id, j, amount);
 				}
 			}
 		}
 	}
+	Some(())
 }
 
 fn finalize_eq_neighbors(
@@ -143,15 +697,74 @@ fn finalize_eq_neighbors(
 	info: &HashMap<TurfID, Cell<MonstermosInfo>>,
 	max_x: i32,
 	max_y: i32,
-) {
+	txn: &mut EqTransaction,
+) -> Option<()> {
 	for (j, adjacent_id) in adjacent_tile_ids(turf.adjacency, i, max_x, max_y) {
 		let amount = transfer_dirs[j as usize];
 		if amount < 0.0 {
 			if let Some(other_turf) = turf_gases().get(&adjacent_id) {
-				finalize_eq(adjacent_id, other_turf.value(), info, max_x, max_y);
+				finalize_eq(adjacent_id, other_turf.value(), info, max_x, max_y, txn)?;
 			}
 		}
 	}
+	Some(())
+}
+
+// Whether finalize_eq can get through the whole zone: every turf it could move gas into or out of, and every
+// turf it could recurse into, still has its monstermos info and its mix.
+fn zone_can_finalize(
+	turfs: &IndexSet<MixWithID>,
+	info: &HashMap<TurfID, Cell<MonstermosInfo>>,
+	max_x: i32,
+	max_y: i32,
+) -> bool {
+	GasArena::with_all_mixtures(|all_mixtures| {
+		let has_mix = |id: TurfID| {
+			turf_gases()
+				.get(&id)
+				.map_or(true, |m| all_mixtures.get(m.mix).is_some())
+		};
+		turfs
+			.iter()
+			.all(|&(i, m)| info.contains_key(&i) && all_mixtures.get(m.mix).is_some())
+			&& info.iter().all(|(&i, cell)| {
+				let transfer_dirs = cell.get().transfer_dirs;
+				has_mix(i)
+					&& turf_gases().get(&i).map_or(true, |m| {
+						adjacent_tile_ids(m.adjacency, i, max_x, max_y).all(|(j, loc)| {
+							transfer_dirs[j as usize] == 0.0
+								|| (info.contains_key(&loc) && has_mix(loc))
+						})
+					})
+			})
+	})
+}
+
+// Finalizes every turf in the zone, or, if any of them can't be, none of them.
+fn finalize_eq_zone(
+	turfs: &IndexSet<MixWithID>,
+	info: &HashMap<TurfID, Cell<MonstermosInfo>>,
+	max_x: i32,
+	max_y: i32,
+	min_transfer: f32,
+	damp_oscillations: bool,
+) -> bool {
+	if !zone_can_finalize(turfs, info, max_x, max_y) {
+		return false;
+	}
+	let mut txn = EqTransaction::new(min_transfer, damp_oscillations);
+	let finished = turfs
+		.iter()
+		.all(|(i, turf)| finalize_eq(*i, turf, info, max_x, max_y, &mut txn).is_some());
+	// whatever did move still gets its pressure differences sent, even in the should-be-impossible case it didn't all go
+	txn.commit();
+	if !finished {
+		return false;
+	}
+	for &(i, _) in turfs.iter() {
+		mark_turf_processed(i);
+	}
+	true
 }
 
 #[cfg(feature = "explosive_decompression")]
@@ -198,6 +811,10 @@ fn explosively_depressurize(
 				let adj_m = {
 					*turf_gases().get(&loc).unwrap()
 				};
+				// Frozen turfs are held exactly as they are; decompression shouldn't reach into them either.
+				if adj_m.frozen() {
+					continue;
+				}
 				if turfs.insert((loc, adj_m)) {
 					unsafe { Value::turf_by_id_unchecked(i) }.call(
 						"consider_firelocks",
@@ -228,7 +845,7 @@ fn explosively_depressurize(
 			};
 			let adj_orig = info.entry(loc).or_default();
 			let mut adj_info = adj_orig.get();
-			if !adj_m.is_immutable() {
+			if !adj_m.is_immutable() && !adj_m.frozen() {
 				if progression_order.insert((loc, adj_m)) {
 					adj_info.curr_transfer_dir = OPP_DIR_INDEX[j as usize];
 					adj_info.curr_transfer_amount = 0.0;
@@ -241,8 +858,8 @@ fn explosively_depressurize(
 			}
 		}
 	}
-	let hpd = auxtools::Value::globals()
-		.get(byond_string!("SSair"))?
+	let ssair = auxtools::Value::globals().get(byond_string!("SSair"))?;
+	let hpd = ssair
 		.get_list(byond_string!("high_pressure_delta"))
 		.map_err(|_| {
 			runtime!(
@@ -252,12 +869,23 @@ fn explosively_depressurize(
 				std::column!()
 			)
 		})?;
+	// Giant breaches get spread over several ticks; whatever's left still has air, so it'll come up again next time.
+	let max_decompression_turfs = ssair
+		.get_number(byond_string!("max_decompression_turfs"))
+		.map_or(usize::MAX, |n| n as usize);
+	let mut decompressed_turfs = 0;
+	let mut deferred_turfs = 0;
 	for (i, m) in progression_order.iter().rev() {
 		let cur_orig = info.entry(*i).or_default();
 		let mut cur_info = cur_orig.get();
 		if cur_info.curr_transfer_dir == 6 {
 			continue;
 		}
+		if decompressed_turfs >= max_decompression_turfs {
+			deferred_turfs += 1;
+			continue;
+		}
+		decompressed_turfs += 1;
 		let mut in_hpd = false;
 		for k in 1..=hpd.len() {
 			if hpd.get(k).unwrap() == unsafe { Value::turf_by_id_unchecked(*i) } {
@@ -307,22 +935,80 @@ fn explosively_depressurize(
 		m.clear_air();
 		byond_turf.call("handle_decompression_floor_rip", &[&Value::from(sum)])?;
 	}
+	ssair.set(
+		byond_string!("num_deferred_decompression_turfs"),
+		Value::from(deferred_turfs as f32),
+	)?;
 	Ok(Value::null())
 	//	if (total_gases_deleted / turfs.len() as f32) > 20.0 && turfs.len() > 10 { // logging I guess
 	//	}
 }
 
+// Everything a flood fill looked at, so we know if doing it again would come out any different.
+#[derive(Default)]
+struct FillFootprint {
+	found: BTreeSet<TurfID>,
+	// If another zone already had a turf we wanted, this zone depends on who went first.
+	blocked_elsewhere: bool,
+}
+
+struct CachedZone {
+	turfs: IndexSet<MixWithID>,
+	planet_turfs: IndexSet<MixWithID>,
+	found: BTreeSet<TurfID>,
+}
+
+#[derive(Default)]
+struct ZoneCache {
+	generation: u64,
+	limits: (usize, usize),
+	zones: HashMap<TurfID, CachedZone>,
+}
+
+impl ZoneCache {
+	// Hands back the zone filled from this turf last time, if filling it now would get the same thing.
+	// Only the moles get recounted.
+	fn reuse(
+		&self,
+		i: TurfID,
+		equalize_turf_limit: usize,
+		found_turfs: &mut BTreeSet<TurfID>,
+		info: &mut HashMap<TurfID, Cell<MonstermosInfo>>,
+	) -> Option<(IndexSet<MixWithID>, IndexSet<MixWithID>, f64)> {
+		let zone = self.zones.get(&i)?;
+		if zone.found.iter().any(|id| found_turfs.contains(id)) {
+			return None;
+		}
+		found_turfs.extend(zone.found.iter().copied());
+		for &id in zone.found.iter() {
+			if id != i && turf_gases().contains_key(&id) {
+				info.entry(id).or_default().take();
+			}
+		}
+		let total_moles = zone
+			.turfs
+			.iter()
+			.take(equalize_turf_limit)
+			.map(|(_, m)| m.total_moles() as f64)
+			.sum();
+		Some((zone.turfs.clone(), zone.planet_turfs.clone(), total_moles))
+	}
+}
+
 fn flood_fill_equalize_turfs(
 	i: TurfID,
 	m: TurfMixture,
 	equalize_turf_limit: usize,
 	equalize_hard_turf_limit: usize,
+	equalize_max_zone_turfs: usize,
 	max_x: i32,
 	max_y: i32,
 	found_turfs: &mut BTreeSet<TurfID>,
 	info: &mut HashMap<TurfID, Cell<MonstermosInfo>>,
+	footprint: &mut FillFootprint,
+	blocking_gas: Option<(u32, f32)>,
 ) -> Option<(IndexSet<MixWithID>, IndexSet<MixWithID>, f64)> {
-	let mut turfs: IndexSet<MixWithID> = IndexSet::with_capacity(equalize_hard_turf_limit);
+	let mut turfs: IndexSet<MixWithID> = IndexSet::with_capacity(equalize_max_zone_turfs);
 	let mut border_turfs: IndexSet<MixWithID> = IndexSet::with_capacity(equalize_turf_limit);
 	let mut planet_turfs: IndexSet<MixWithID> = IndexSet::new();
 	#[cfg(feature = "explosive_decompression")]
@@ -330,10 +1016,11 @@ fn flood_fill_equalize_turfs(
 	let mut total_moles = 0.0_f64;
 	border_turfs.insert((i, m));
 	found_turfs.insert(i);
+	footprint.found.insert(i);
 	#[allow(unused_mut)]
 	let mut space_this_time = false;
 	loop {
-		if turfs.len() >= equalize_hard_turf_limit {
+		if turfs.len() >= equalize_max_zone_turfs {
 			break;
 		}
 		if let Some((cur_idx, cur_turf)) = border_turfs.shift_remove_index(0 as usize) {
@@ -345,11 +1032,21 @@ fn flood_fill_equalize_turfs(
 				total_moles += cur_turf.total_moles() as f64;
 			}
 			for (_, loc) in adjacent_tile_ids(cur_turf.adjacency, cur_idx, max_x, max_y) {
+				if blocking_gas.is_some()
+					&& turf_gases().get(&loc).map_or(false, |adj_turf| {
+						turf_blocks_equalization(adj_turf.value(), blocking_gas)
+					}) {
+					continue;
+				}
 				if found_turfs.insert(loc) {
+					footprint.found.insert(loc);
 					if let Some(adj_turf) = turf_gases().get(&loc) {
 						let adj_orig = info.entry(loc).or_default();
 						#[cfg(feature = "explosive_decompression")]
 						{
+							if adj_turf.frozen() {
+								continue;
+							}
 							adj_orig.take();
 							border_turfs.insert((loc, *adj_turf.value()));
 							if adj_turf.value().is_immutable() {
@@ -386,6 +1083,8 @@ fn flood_fill_equalize_turfs(
 							}
 						}
 					}
+				} else {
+					footprint.blocked_elsewhere |= !footprint.found.contains(&loc);
 				}
 				if space_this_time {
 					break;
@@ -684,15 +1383,52 @@ fn process_planet_turfs(
 pub(crate) fn equalize(
 	equalize_turf_limit: usize,
 	equalize_hard_turf_limit: usize,
+	equalize_max_zone_turfs: usize,
+	equalize_min_transfer: f32,
+	profile_zones: bool,
+	damp_oscillations: bool,
+	blocking_gas: Option<(u32, f32)>,
 	max_x: i32,
 	max_y: i32,
 	high_pressure_turfs: BTreeSet<TurfID>,
 ) -> usize {
+	*TURF_GAS_FLOW.write() = None;
+	*TURF_WIND.write() = None;
+	*EQ_GAS_FLOW.write() = None;
+	let debug_turf = DEBUG_EQ_GRAPH.read().as_ref().map(|&(turf, _)| turf);
+	let tick = EQUALIZE_TICK.fetch_add(1, Ordering::Relaxed) + 1;
+	if let Some(history) = EDGE_HISTORY.write().as_mut() {
+		history.retain(|_, edge| edge.tick + 1 >= tick);
+	}
+	let mut zone_deltas_guard = ZONE_DELTAS.write();
+	let zone_deltas = zone_deltas_guard.get_or_insert_with(HashMap::new);
+	zone_deltas.retain(|_, &mut (last_tick, _, _)| last_tick + 1 >= tick);
+	let mut zone_cache_guard = ZONE_CACHE.write();
+	let zone_cache = zone_cache_guard.get_or_insert_with(ZoneCache::default);
+	let generation = topology_generation();
+	let limits = (equalize_turf_limit, equalize_max_zone_turfs);
+	if zone_cache.generation != generation || zone_cache.limits != limits {
+		zone_cache.zones.clear();
+		zone_cache.generation = generation;
+		zone_cache.limits = limits;
+	}
 	let mut info: HashMap<TurfID, Cell<MonstermosInfo>> = HashMap::new();
 	let mut turfs_processed = 0;
+	let mut turfs_examined = 0;
 	let mut queue_cycle_slow = 1;
 	let mut found_turfs: BTreeSet<TurfID> = BTreeSet::new();
-	for &i in high_pressure_turfs.iter() {
+	// zone size and microseconds taken, for each zone equalized
+	let mut zone_timings: Vec<(usize, u128)> = Vec::new();
+	let mut deferred_turfs = 0;
+	for (n, &i) in high_pressure_turfs.iter().enumerate() {
+		if turfs_examined >= equalize_hard_turf_limit {
+			deferred_turfs = high_pressure_turfs
+				.iter()
+				.skip(n)
+				.filter(|&&idx| !found_turfs.contains(&idx))
+				.count();
+			break;
+		}
 		if found_turfs.contains(&i)
 			|| turf_gases().get(&i).map_or(true, |m| {
 				!m.enabled()
@@ -707,27 +1443,61 @@ pub(crate) fn equalize(
 			}) {
 			continue;
 		}
-		let m = turf_gases().get(&i).unwrap();
-		let maybe_turfs = flood_fill_equalize_turfs(
-			i,
-			*m,
-			equalize_turf_limit,
-			equalize_hard_turf_limit,
-			max_x,
-			max_y,
-			&mut found_turfs,
-			&mut info,
-		);
+		if blocking_gas.is_some()
+			&& turf_gases()
+				.get(&i)
+				.map_or(false, |m| turf_blocks_equalization(m.value(), blocking_gas))
+		{
+			continue;
+		}
+		let zone_start = profile_zones.then(Instant::now);
+		// whether a turf blocks can change without the topology changing, so cached zones can't be trusted then
+		let maybe_turfs = blocking_gas
+			.is_none()
+			.then(|| zone_cache.reuse(i, equalize_turf_limit, &mut found_turfs, &mut info))
+			.flatten()
+			.or_else(|| {
+				let m = turf_gases().get(&i).unwrap();
+				let mut footprint = FillFootprint::default();
+				let filled = flood_fill_equalize_turfs(
+					i,
+					*m,
+					equalize_turf_limit,
+					equalize_hard_turf_limit,
+					equalize_max_zone_turfs,
+					max_x,
+					max_y,
+					&mut found_turfs,
+					&mut info,
+					&mut footprint,
+					blocking_gas,
+				);
+				if let Some((turfs, planet_turfs, _)) = &filled {
+					if !footprint.blocked_elsewhere && blocking_gas.is_none() {
+						zone_cache.zones.insert(
+							i,
+							CachedZone {
+								turfs: turfs.clone(),
+								planet_turfs: planet_turfs.clone(),
+								found: footprint.found,
+							},
+						);
+					}
+				}
+				filled
+			});
 		if maybe_turfs.is_none() {
 			continue;
 		}
 		let (mut turfs, planet_turfs, total_moles) = maybe_turfs.unwrap();
+		turfs_examined += turfs.len();
 		if turfs.len() > equalize_turf_limit {
 			// throw out any above turf limit, we check more for explosive decomp
 			for (idx, _) in turfs.drain(equalize_turf_limit..) {
 				found_turfs.remove(&idx);
 			}
 		}
+		let zone_size = turfs.len();
 		let average_moles = (total_moles / (turfs.len() - planet_turfs.len()) as f64) as f32;
 		let (mut giver_turfs, mut taker_turfs): (Vec<_>, Vec<_>) =
 			turfs.iter().partition(|&(i, m)| {
@@ -735,13 +1505,23 @@ pub(crate) fn equalize(
 				cur_info.mole_delta = m.total_moles() - average_moles;
 				cur_info.mole_delta > 0.0
 			});
+		for &(i, _) in turfs.iter() {
+			let delta = info.get(&i).map_or(0.0, |c| c.get().mole_delta);
+			let prev_delta = zone_deltas
+				.get(&i)
+				.filter(|&&(last_tick, _, _)| last_tick + 1 == tick)
+				.map(|&(_, prev, _)| prev);
+			zone_deltas.insert(i, (tick, delta, prev_delta));
+		}
 		let log_n = ((turfs.len() as f32).log2().floor()) as usize;
 		if giver_turfs.len() > log_n && taker_turfs.len() > log_n {
 			turfs.sort_by(|idx, idy| {
 				let (x, _) = idx;
 				let (y, _) = idy;
 				float_ord::FloatOrd(info.get(x).unwrap().get().mole_delta)
-					.cmp(&float_ord::FloatOrd(info.get(y).unwrap().get().mole_delta)).reverse()
+					.cmp(&float_ord::FloatOrd(info.get(y).unwrap().get().mole_delta))
+					.reverse()
+					.then_with(|| x.cmp(y))
 			});
 			for &(i, m) in &turfs {
 				monstermos_fast_process(i, m, max_x, max_y, &mut info);
@@ -756,6 +1536,9 @@ pub(crate) fn equalize(
 				}
 			}
 		}
+		// Go in turf order rather than whatever order the zone was filled in, so the same air always equalizes the same way.
+		giver_turfs.sort_unstable_by_key(|&(i, _)| i);
+		taker_turfs.sort_unstable_by_key(|&(i, _)| i);
 		// alright this is the part that can become O(n^2).
 		if giver_turfs.len() < taker_turfs.len() {
 			// as an optimization, we choose one of two methods based on which list is smaller.
@@ -777,6 +1560,28 @@ pub(crate) fn equalize(
 				&mut queue_cycle_slow,
 			);
 		}
+		if let Some(debug_turf) = debug_turf {
+			if turfs.iter().any(|&(i, _)| i == debug_turf) {
+				let mut edges = Vec::new();
+				for &(i, m) in turfs.iter() {
+					let transfer_dirs = info.get(&i).map_or([0.0; 7], |c| c.get().transfer_dirs);
+					for (j, loc) in adjacent_tile_ids(m.adjacency, i, max_x, max_y) {
+						let amount = transfer_dirs[j as usize];
+						if amount > 0.0 {
+							edges.push((i, loc, amount));
+						}
+					}
+				}
+				let mut debug_graph = DEBUG_EQ_GRAPH.write();
+				// unless something else started being watched in the meantime
+				if debug_graph
+					.as_ref()
+					.map_or(false, |(watched, _)| *watched == debug_turf)
+				{
+					*debug_graph = Some((debug_turf, Some(edges)));
+				}
+			}
+		}
 		if !planet_turfs.is_empty() {
 			turfs_processed += turfs.len() + planet_turfs.len();
 			let sender = byond_callback_sender();
@@ -797,17 +1602,46 @@ pub(crate) fn equalize(
 					&mut cloned,
 					queue_cycle_slow,
 				)?;
-				for (i, turf) in turfs.iter() {
-					finalize_eq(*i, turf, &cloned, max_x, max_y);
-				}
+				finalize_eq_zone(
+					&turfs,
+					&cloned,
+					max_x,
+					max_y,
+					equalize_min_transfer,
+					damp_oscillations,
+				);
 				Ok(Value::null())
 			}));
 		} else {
-			turfs_processed += turfs.len();
-			for (i, turf) in turfs.iter() {
-				finalize_eq(*i, turf, &info, max_x, max_y);
+			if finalize_eq_zone(
+				&turfs,
+				&info,
+				max_x,
+				max_y,
+				equalize_min_transfer,
+				damp_oscillations,
+			) {
+				turfs_processed += turfs.len();
 			}
 		}
+		if let Some(start) = zone_start {
+			zone_timings.push((zone_size, start.elapsed().as_micros()));
+		}
+	}
+	DEFERRED_TURFS.store(deferred_turfs as u64, Ordering::Relaxed);
+	if profile_zones {
+		let sender = byond_callback_sender();
+		send_callback!(sender, move || {
+			let timings = List::new();
+			for &(size, micros) in &zone_timings {
+				timings.append(Value::from(size as f32));
+				timings.append(Value::from(micros as f32));
+			}
+			auxtools::Value::globals()
+				.get(byond_string!("SSair"))?
+				.set(byond_string!("equalize_zone_timings"), Value::from(timings))?;
+			Ok(Value::null())
+		});
 	}
 	turfs_processed
 }
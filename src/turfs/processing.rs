@@ -10,7 +10,7 @@ use std::time::{Duration, Instant};
 
 use auxcallback::{byond_callback_sender, process_callbacks_for_millis};
 
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
 
 use parking_lot::RwLock;
 
@@ -20,15 +20,58 @@ const PROCESS_PROCESSING: u8 = 1;
 
 const PROCESS_DONE: u8 = 2;
 
+/*
+	When the reaction pass runs, relative to equalization; SSair's reaction_order.
+	Reacting after means gas spreads first and then lights, so fires start where the gas ended up.
+	Reacting before means fires light where the gas was, then equalization spreads the hot gas around.
+	Reactions run on the main thread, so reacting before waits for them to finish before equalizing,
+	up to REACTION_WAIT_TIMEOUT; if byond's too busy to get to them by then, equalization goes ahead anyway.
+	The pass also handles visuals, so anything that skips the after pass shows up a tick late.
+*/
+const REACT_AFTER_EQUALIZE: u8 = 0;
+
+const REACT_BEFORE_EQUALIZE: u8 = 1;
+
+const REACT_BEFORE_AND_AFTER_EQUALIZE: u8 = 2;
+
+const REACTION_WAIT_TIMEOUT: Duration = Duration::from_secs(1);
+
 static PROCESSING_TURF_STEP: AtomicU8 = AtomicU8::new(PROCESS_NOT_STARTED);
 
 static WAITING_FOR_THREAD: AtomicBool = AtomicBool::new(false);
 
+// The high pressure turfs last handed to equalization, kept around for debugging.
+static PENDING_EQUALIZE_TURFS: RwLock<Vec<TurfID>> = parking_lot::const_rwlock(Vec::new());
+
+// Turfs that reacted since the last reaction pass reported in.
+static REACTING_TURFS: AtomicU64 = AtomicU64::new(0);
+
+// The last turf react_all_turfs got to before it ran out of time, so the next call picks up after it.
+static REACT_SWEEP_CURSOR: AtomicU32 = AtomicU32::new(0);
+
+// Which slice of turfs gets its visuals checked next post process.
+static VISUAL_SLICE_CURSOR: AtomicU32 = AtomicU32::new(0);
+
+// How many ticks in a row each turf that's gone quiet has stayed that way; only kept while settling is on.
+static SETTLE_COUNTS: RwLock<Option<DashMap<TurfID, u32, FxBuildHasher>>> =
+	parking_lot::const_rwlock(None);
+
+pub(crate) fn thread_running() -> bool {
+	PROCESSING_TURF_STEP.load(Ordering::Relaxed) == PROCESS_PROCESSING
+}
+
 #[hook("/datum/controller/subsystem/air/proc/thread_running")]
 fn _thread_running_hook() {
-	Ok(Value::from(
-		PROCESSING_TURF_STEP.load(Ordering::Relaxed) == PROCESS_PROCESSING,
-	))
+	Ok(Value::from(thread_running()))
+}
+
+#[hook("/proc/pending_equalize_turfs")]
+fn _pending_equalize_turfs_hook() {
+	let ret = List::new();
+	for &id in PENDING_EQUALIZE_TURFS.read().iter() {
+		ret.append(Value::from(id as f32));
+	}
+	Ok(Value::from(ret))
 }
 
 #[hook("/datum/controller/subsystem/air/proc/finish_turf_processing_auxtools")]
@@ -83,6 +126,9 @@ fn _process_turf_hook() {
 		let fdm_max_steps = src
 			.get_number(byond_string!("share_max_steps"))
 			.unwrap_or(1.0) as i32;
+		let settle_ticks = src
+			.get_number(byond_string!("turf_settle_ticks"))
+			.map_or(0, |n| n.max(0.0) as u32);
 		let equalize_turf_limit = src
 			.get_number(byond_string!("equalize_turf_limit"))
 			.map_err(|_| {
@@ -103,6 +149,56 @@ fn _process_turf_hook() {
 					std::column!()
 				)
 			})? as usize;
+		// How big any one zone can get; past this, turfs are just the zone's border.
+		// The hard turf limit is the budget for every zone this tick put together.
+		let equalize_max_zone_turfs = src
+			.get_number(byond_string!("equalize_max_zone_turfs"))
+			.map_or(equalize_hard_turf_limit, |n| n as usize);
+		// Monstermos won't bother moving less gas than this across any one edge.
+		let equalize_min_transfer = src
+			.get_number(byond_string!("equalize_min_transfer"))
+			.map_or(GAS_MIN_MOLES, |n| n.max(0.0));
+		// For profiling: has monstermos time each zone it equalizes and report the times back.
+		let equalize_profile_zones = src
+			.get_number(byond_string!("equalize_profile_zones"))
+			.map_or(false, |n| n != 0.0);
+		// Halves monstermos transfers across edges where gas keeps going back and forth tick after tick.
+		let equalize_damp_oscillations = src
+			.get_number(byond_string!("equalize_damp_oscillations"))
+			.map_or(false, |n| n != 0.0);
+		// Turfs where at least this fraction of the gas is in the blocking category act as walls for equalization.
+		// No blocking category means nothing blocks.
+		let equalize_blocking_gas = src
+			.get_number(byond_string!("equalize_blocking_category"))
+			.ok()
+			.map(|n| n as u32)
+			.filter(|&category| category != 0)
+			.map(|category| {
+				(
+					category,
+					src.get_number(byond_string!("equalize_blocking_threshold"))
+						.unwrap_or(0.5),
+				)
+			});
+		// Empties out turf air with a broken temperature or moles before anything shares it along. Off by default.
+		let repair_corrupt_mixtures = src
+			.get_number(byond_string!("repair_corrupt_mixtures"))
+			.map_or(false, |n| n != 0.0);
+		// How much of the way back to their targets gas sources get each tick; 1 refills them outright.
+		let gas_source_refill_rate = src
+			.get_number(byond_string!("gas_source_refill_rate"))
+			.map_or(1.0, |n| n.clamp(0.0, 1.0));
+		// Only 1/visual_update_slices of turfs get their visuals checked each tick, taking turns.
+		let visual_update_slices = src
+			.get_number(byond_string!("visual_update_slices"))
+			.map_or(1, |n| n.max(1.0) as u32);
+		// Keeps track of when each turf's air last changed, for turf_air_staleness. Off by default.
+		let track_air_staleness = src
+			.get_number(byond_string!("track_air_staleness"))
+			.map_or(false, |n| n != 0.0);
+		let reaction_order = src
+			.get_number(byond_string!("reaction_order"))
+			.map_or(REACT_AFTER_EQUALIZE, |n| n as u8);
 		let equalize_enabled = cfg!(feature = "equalization")
 			&& src
 				.get_number(byond_string!("equalize_enabled"))
@@ -124,35 +220,27 @@ fn _process_turf_hook() {
 					std::column!()
 				)
 			})?;
-		let max_x = auxtools::Value::world()
-			.get_number(byond_string!("maxx"))
-			.map_err(|_| {
-				runtime!(
-					"Attempt to interpret non-number value as number {} {}:{}",
-					std::file!(),
-					std::line!(),
-					std::column!()
-				)
-			})? as i32;
-		let max_y = auxtools::Value::world()
-			.get_number(byond_string!("maxy"))
-			.map_err(|_| {
-				runtime!(
-					"Attempt to interpret non-number value as number {} {}:{}",
-					std::file!(),
-					std::line!(),
-					std::column!()
-				)
-			})? as i32;
+		let (max_x, max_y) = world_dimensions()?;
+		BLOCKING_CALLBACKS.store(
+			src.get_number(byond_string!("blocking_callbacks"))
+				.map_or(false, |n| n != 0.0),
+			Ordering::Relaxed,
+		);
 		rayon::spawn(move || {
 			PROCESSING_TURF_STEP.store(PROCESS_PROCESSING, Ordering::SeqCst);
+			advance_processing_tick();
+			set_tracking_air_changes(track_air_staleness);
 			let sender = byond_callback_sender();
+			if repair_corrupt_mixtures {
+				repair_corrupt_turfs();
+			}
 			let (low_pressure_turfs, high_pressure_turfs) = {
 				let start_time = Instant::now();
-				let (low_pressure_turfs, high_pressure_turfs) = fdm(max_x, max_y, fdm_max_steps);
+				let (low_pressure_turfs, high_pressure_turfs) =
+					fdm(max_x, max_y, fdm_max_steps, settle_ticks);
 				let bench = start_time.elapsed().as_millis();
 				let (lpt, hpt) = (low_pressure_turfs.len(), high_pressure_turfs.len());
-				let _ = sender.try_send(Box::new(move || {
+				send_callback!(sender, move || {
 					let ssair = auxtools::Value::globals().get(byond_string!("SSair"))?;
 					let prev_cost =
 						ssair.get_number(byond_string!("cost_turfs")).map_err(|_| {
@@ -173,7 +261,7 @@ fn _process_turf_hook() {
 						Value::from(hpt as f32),
 					)?;
 					Ok(Value::null())
-				}));
+				});
 				(low_pressure_turfs, high_pressure_turfs)
 			};
 			{
@@ -185,7 +273,7 @@ fn _process_turf_hook() {
 					&low_pressure_turfs,
 				);
 				let bench = start_time.elapsed().as_millis();
-				let _ = sender.try_send(Box::new(move || {
+				send_callback!(sender, move || {
 					let ssair = auxtools::Value::globals().get(byond_string!("SSair"))?;
 					let prev_cost =
 						ssair
@@ -207,9 +295,16 @@ fn _process_turf_hook() {
 						Value::from(processed_turfs as f32),
 					)?;
 					Ok(Value::null())
-				}));
+				});
+			}
+			if reaction_order == REACT_BEFORE_EQUALIZE
+				|| reaction_order == REACT_BEFORE_AND_AFTER_EQUALIZE
+			{
+				timed_post_process(visual_update_slices);
+				wait_for_callbacks(REACTION_WAIT_TIMEOUT);
 			}
 			if equalize_enabled {
+				*PENDING_EQUALIZE_TURFS.write() = high_pressure_turfs.iter().copied().collect();
 				let start_time = Instant::now();
 				let processed_turfs = {
 					#[cfg(feature = "putnamos")]
@@ -217,6 +312,7 @@ fn _process_turf_hook() {
 						super::putnamos::equalize(
 							equalize_turf_limit,
 							equalize_hard_turf_limit,
+							equalize_max_zone_turfs,
 							max_x,
 							max_y,
 							high_pressure_turfs,
@@ -227,6 +323,11 @@ fn _process_turf_hook() {
 						super::monstermos::equalize(
 							equalize_turf_limit,
 							equalize_hard_turf_limit,
+							equalize_max_zone_turfs,
+							equalize_min_transfer,
+							equalize_profile_zones,
+							equalize_damp_oscillations,
+							equalize_blocking_gas,
 							max_x,
 							max_y,
 							high_pressure_turfs,
@@ -238,7 +339,35 @@ fn _process_turf_hook() {
 					}
 				};
 				let bench = start_time.elapsed().as_millis();
-				let _ = sender.try_send(Box::new(move || {
+				#[cfg(feature = "monstermos")]
+				let deferred_turfs = super::monstermos::deferred_equalize_turfs();
+				#[cfg(not(feature = "monstermos"))]
+				let deferred_turfs = 0;
+				// how much of the work queued for equalization got done, for pacing on the byond end
+				let progress = if processed_turfs + deferred_turfs > 0 {
+					processed_turfs as f32 / (processed_turfs + deferred_turfs) as f32
+				} else {
+					1.0
+				};
+				// only what equalization itself moved counts, so reactions and byond don't throw this off
+				#[cfg(all(debug_assertions, feature = "monstermos"))]
+				{
+					let (delta, moved) = super::monstermos::take_equalize_mole_balance();
+					if delta.abs() > (moved * 0.0001).max(0.01) {
+						send_callback!(sender, move || {
+							if let Some(stack_trace) =
+								Proc::find(byond_string!("/proc/stack_trace"))
+							{
+								stack_trace.call(&[&Value::from_string(&format!(
+									"Equalization changed the total moles by {}",
+									delta
+								))?])?;
+							}
+							Ok(Value::null())
+						});
+					}
+				}
+				send_callback!(sender, move || {
 					let ssair = auxtools::Value::globals().get(byond_string!("SSair"))?;
 					let prev_cost =
 						ssair
@@ -259,31 +388,14 @@ fn _process_turf_hook() {
 						byond_string!("num_equalize_processed"),
 						Value::from(processed_turfs as f32),
 					)?;
+					ssair.set(byond_string!("equalize_progress"), Value::from(progress))?;
 					Ok(Value::null())
-				}));
+				});
 			}
-			{
-				let start_time = Instant::now();
-				post_process();
-				let bench = start_time.elapsed().as_millis();
-				let _ = sender.try_send(Box::new(move || {
-					let ssair = auxtools::Value::globals().get(byond_string!("SSair"))?;
-					let prev_cost = ssair
-						.get_number(byond_string!("cost_post_process"))
-						.map_err(|_| {
-							runtime!(
-								"Attempt to interpret non-number value as number {} {}:{}",
-								std::file!(),
-								std::line!(),
-								std::column!()
-							)
-						})?;
-					ssair.set(
-						byond_string!("cost_post_process"),
-						Value::from(0.8 * prev_cost + 0.2 * (bench as f32)),
-					)?;
-					Ok(Value::null())
-				}));
+			// after equalization, so sources it drained this tick are back up before anything reads them
+			crate::gas::refill_gas_sources(gas_source_refill_rate);
+			if reaction_order != REACT_BEFORE_EQUALIZE {
+				timed_post_process(visual_update_slices);
 			}
 			PROCESSING_TURF_STEP.store(PROCESS_DONE, Ordering::SeqCst);
 		});
@@ -291,6 +403,90 @@ fn _process_turf_hook() {
 	Ok(Value::from(false))
 }
 
+// Resets every turf mix gone corrupt, so it can't get merged into the rest of its zone, and says where each one was.
+fn repair_corrupt_turfs() {
+	let repaired = GasArena::with_all_mixtures(|all_mixtures| {
+		turf_gases()
+			.shards()
+			.par_iter()
+			.map(|shard| {
+				shard
+					.read()
+					.iter()
+					.filter_map(|(&i, m_v)| {
+						let m = *m_v.get();
+						let lock = all_mixtures.get(m.mix)?;
+						if lock.read().audit(f32::INFINITY).is_none() {
+							return None;
+						}
+						lock.write().reset_if_corrupt().then(|| (i, m.mix))
+					})
+					.collect::<Vec<_>>()
+			})
+			.flatten()
+			.collect::<Vec<_>>()
+	});
+	if repaired.is_empty() {
+		return;
+	}
+	send_callback!(byond_callback_sender(), move || {
+		if let Some(stack_trace) = Proc::find(byond_string!("/proc/stack_trace")) {
+			for &(i, mix) in &repaired {
+				let turf = unsafe { Value::turf_by_id_unchecked(i) };
+				stack_trace.call(&[&Value::from_string(&format!(
+					"Reset corrupt air on turf at ({}, {}, {}), gas mixture {}",
+					turf.get_number(byond_string!("x")).unwrap_or_default(),
+					turf.get_number(byond_string!("y")).unwrap_or_default(),
+					turf.get_number(byond_string!("z")).unwrap_or_default(),
+					mix
+				))?])?;
+			}
+		}
+		Ok(Value::null())
+	});
+}
+
+// Blocks until byond's run every callback queued before now, or the timeout's up. Never call it from the main thread.
+// If the marker callback can't be queued, it's dropped along with the other end of the channel, so this returns right away.
+fn wait_for_callbacks(timeout: Duration) {
+	let (done_tx, done_rx) = flume::bounded(1);
+	send_callback!(byond_callback_sender(), move || {
+		let _ = done_tx.send(());
+		Ok(Value::null())
+	});
+	let _ = done_rx.recv_timeout(timeout);
+}
+
+// The reaction and visuals pass, plus reporting how long it took.
+fn timed_post_process(visual_update_slices: u32) {
+	let start_time = Instant::now();
+	post_process(visual_update_slices);
+	let bench = start_time.elapsed().as_millis();
+	send_callback!(byond_callback_sender(), move || {
+		let ssair = auxtools::Value::globals().get(byond_string!("SSair"))?;
+		let prev_cost = ssair
+			.get_number(byond_string!("cost_post_process"))
+			.map_err(|_| {
+				runtime!(
+					"Attempt to interpret non-number value as number {} {}:{}",
+					std::file!(),
+					std::line!(),
+					std::column!()
+				)
+			})?;
+		ssair.set(
+			byond_string!("cost_post_process"),
+			Value::from(0.8 * prev_cost + 0.2 * (bench as f32)),
+		)?;
+		// sent after every reaction callback the pass made, so they've all been counted by now
+		ssair.set(
+			byond_string!("num_reacting_turfs"),
+			Value::from(REACTING_TURFS.swap(0, Ordering::Relaxed) as f32),
+		)?;
+		Ok(Value::null())
+	});
+}
+
 // Compares with neighbors, returning early if any of them are valid.
 fn should_process(m: TurfMixture, all_mixtures: &[RwLock<Mixture>]) -> bool {
 	m.adjacency > 0
@@ -320,6 +516,29 @@ fn should_process(m: TurfMixture, all_mixtures: &[RwLock<Mixture>]) -> bool {
 			})
 }
 
+/*
+	Hysteresis for turfs that have gone quiet: they keep processing until they've been quiet for
+	settle_ticks ticks in a row, so a turf hovering right at the threshold doesn't drop in and out
+	every tick. Anything worth moving, including gas showing up mid-settle, resets the count.
+*/
+fn keep_settling(
+	counts: &DashMap<TurfID, u32, FxBuildHasher>,
+	i: TurfID,
+	active: bool,
+	settle_ticks: u32,
+	new_tick: bool,
+) -> bool {
+	if active {
+		counts.remove(&i);
+		return true;
+	}
+	let mut quiet_ticks = counts.entry(i).or_insert(0);
+	if new_tick {
+		*quiet_ticks = quiet_ticks.saturating_add(1);
+	}
+	*quiet_ticks <= settle_ticks
+}
+
 // Creates the combined gas mixture of all this mix's neighbors, as well as gathering some other pertinent info for future processing.
 fn process_cell(
 	i: TurfID,
@@ -347,6 +566,10 @@ fn process_cell(
 		Technically that's ρν², but, like, video games.
 	*/
 	for (j, loc, entry) in m.adjacent_mixes_with_adj_info(all_mixtures, i, max_x, max_y) {
+		// frozen turfs are walls as far as sharing goes
+		if m.adjacents[j].map_or(false, |adj| is_frozen_mix(adj.get())) {
+			continue;
+		}
 		match entry.try_read() {
 			Some(mix) => {
 				end_gas.merge(&mix);
@@ -381,7 +604,12 @@ fn process_cell(
 }
 
 // Solving the heat equation using a Finite Difference Method, an iterative stencil loop.
-fn fdm(max_x: i32, max_y: i32, fdm_max_steps: i32) -> (BTreeSet<TurfID>, BTreeSet<TurfID>) {
+fn fdm(
+	max_x: i32,
+	max_y: i32,
+	fdm_max_steps: i32,
+	settle_ticks: u32,
+) -> (BTreeSet<TurfID>, BTreeSet<TurfID>) {
 	/*
 		This is the replacement system for LINDA. LINDA requires a lot of bookkeeping,
 		which, when coefficient-wise operations are this fast, is all just unnecessary overhead.
@@ -389,6 +617,15 @@ fn fdm(max_x: i32, max_y: i32, fdm_max_steps: i32) -> (BTreeSet<TurfID>, BTreeSe
 		sleeping turfs, which is why I've renamed it to fdm.
 	*/
 	PROCESSING_TURF_STEP.store(PROCESS_PROCESSING, Ordering::SeqCst);
+	{
+		let mut settle_counts = SETTLE_COUNTS.write();
+		if settle_ticks == 0 {
+			*settle_counts = None;
+		} else if settle_counts.is_none() {
+			*settle_counts = Some(DashMap::with_hasher(FxBuildHasher::default()));
+		}
+	}
+	let settle_counts = SETTLE_COUNTS.read();
 	let mut low_pressure_turfs: BTreeSet<TurfID> = BTreeSet::new();
 	let mut high_pressure_turfs: BTreeSet<TurfID> = BTreeSet::new();
 	let mut cur_count = 1;
@@ -415,7 +652,15 @@ fn fdm(max_x: i32, max_y: i32, fdm_max_steps: i32) -> (BTreeSet<TurfID>, BTreeSe
 						.read()
 						.iter()
 						.map(|(i, m_v)| (i, *m_v.get()))
-						.filter(|&(_, m)| should_process(m, all_mixtures))
+						.filter(|&(&i, m)| {
+							let active = should_process(m, all_mixtures);
+							match settle_counts.as_ref() {
+								Some(counts) if m.adjacency > 0 && m.enabled() => {
+									keep_settling(counts, i, active, settle_ticks, cur_count == 1)
+								}
+								_ => active,
+							}
+						})
 						.filter_map(|(&i, m)| process_cell(i, m, max_x, max_y, all_mixtures))
 						.collect::<Vec<_>>()
 				})
@@ -478,7 +723,7 @@ fn fdm(max_x: i32, max_y: i32, fdm_max_steps: i32) -> (BTreeSet<TurfID>, BTreeSe
 				.for_each(|temp_value| {
 					let sender = byond_callback_sender();
 					let these_pressure_deltas = temp_value.iter().copied().collect::<Vec<_>>();
-					let _ = sender.try_send(Box::new(move || {
+					send_callback!(sender, move || {
 						for &(turf_id, pressure_diffs, _) in
 							these_pressure_deltas.iter().filter(|&(id, _, _)| *id != 0)
 						{
@@ -501,7 +746,7 @@ fn fdm(max_x: i32, max_y: i32, fdm_max_steps: i32) -> (BTreeSet<TurfID>, BTreeSe
 							}
 						}
 						Ok(Value::null())
-					}));
+					});
 				});
 			high_pressure_turfs.extend(high_pressure.iter().map(|(i, _, _)| i));
 			low_pressure_turfs.extend(low_pressure.iter().map(|(i, _, _)| i));
@@ -558,6 +803,7 @@ fn excited_group_processing(
 							if let Some(border_mix) = turf_gases().get(&loc) {
 								if border_mix.simulation_level & SIMULATION_LEVEL_DISABLED
 									!= SIMULATION_LEVEL_DISABLED
+									&& !border_mix.frozen()
 								{
 									border_turfs.push_back((loc, *border_mix));
 								}
@@ -603,25 +849,47 @@ fn remove_trace_planet_gases(
 }
 
 // Checks if the gas can react or can update visuals, returns None if not.
+// Visuals are only looked at if check_visuals is set; the cached hash stays put otherwise,
+// so a change is still caught whenever the turf's turn comes up.
 fn post_process_cell(
 	i: TurfID,
 	m: TurfMixture,
 	vis: &[Option<f32>],
+	check_visuals: bool,
+	air_changes: Option<&DashMap<TurfID, (u64, u64), FxBuildHasher>>,
 	all_mixtures: &[RwLock<Mixture>],
 ) -> Option<(TurfID, bool, bool)> {
 	all_mixtures
 		.get(m.mix)
 		.and_then(RwLock::try_read)
 		.and_then(|gas| {
-			let should_update_visuals = gas.vis_hash_changed(vis);
+			if let Some(changes) = air_changes {
+				note_air_composition(changes, i, &gas);
+			}
+			let should_update_visuals = check_visuals && gas.vis_hash_changed(vis);
 			let reactable = gas.can_react();
 			(should_update_visuals || reactable).then(|| (i, should_update_visuals, reactable))
 		})
 }
 
+// Reacts a turf's air, counting it if anything actually happened.
+fn react_turf(i: TurfID) -> Result<(), Runtime> {
+	let turf = unsafe { Value::turf_by_id_unchecked(i) };
+	let reacted = if cfg!(target_os = "linux") {
+		turf.get(byond_string!("air"))?.call("vv_react", &[&turf])?
+	} else {
+		turf.get(byond_string!("air"))?.call("react", &[&turf])?
+	};
+	if reacted.as_number().unwrap_or_default() != 0.0 {
+		REACTING_TURFS.fetch_add(1, Ordering::Relaxed);
+	}
+	Ok(())
+}
+
 // Goes through every turf, checks if it should reset to planet atmos, if it should
 // update visuals, if it should react, sends a callback if it should.
-fn post_process() {
+// Visuals are checked for one of visual_update_slices slices of turfs per call, round-robin.
+fn post_process(visual_update_slices: u32) {
 	let should_check_planet_turfs = unsafe {
 		let timer = PLANET_RESET_TIMER.get_or_insert_with(Instant::now);
 		if timer.elapsed() > Duration::from_secs(5) {
@@ -632,6 +900,9 @@ fn post_process() {
 		}
 	};
 	let vis = crate::gas::visibility_copies();
+	let visual_update_slices = visual_update_slices.max(1);
+	let visual_slice = VISUAL_SLICE_CURSOR.fetch_add(1, Ordering::Relaxed) % visual_update_slices;
+	let air_changes = AIR_CHANGE_TICKS.read();
 	turf_gases().shards().par_iter().for_each(|shard| {
 		let sender = byond_callback_sender();
 		let mut reacters = VecDeque::with_capacity(10);
@@ -652,8 +923,18 @@ fn post_process() {
 				.iter()
 				.filter_map(|(&i, m_v)| {
 					let m = m_v.get();
+					let check_visuals = i % visual_update_slices == visual_slice;
 					m.enabled()
-						.then(|| post_process_cell(i, *m, &vis, all_mixtures))
+						.then(|| {
+							post_process_cell(
+								i,
+								*m,
+								&vis,
+								check_visuals,
+								air_changes.as_ref(),
+								all_mixtures,
+							)
+						})
 						.flatten()
 				})
 				.for_each(|(i, should_update_visuals, reactable)| {
@@ -661,53 +942,112 @@ fn post_process() {
 						visual_updaters.push_back(i);
 						if visual_updaters.len() >= 25 {
 							let copy = visual_updaters.drain(..).collect::<Vec<_>>();
-							let _ = sender.try_send(Box::new(move || {
+							send_callback!(sender, move || {
 								for &i in &copy {
 									let turf = unsafe { Value::turf_by_id_unchecked(i) };
 									turf.call("update_visuals", &[])?;
 								}
 								Ok(Value::null())
-							}));
+							});
 						}
 					}
 					if reactable {
+						mark_turf_processed(i);
 						reacters.push_back(i);
 						if reacters.len() >= 10 {
 							let copy = reacters.drain(..).collect::<Vec<_>>();
-							let _ = sender.try_send(Box::new(move || {
+							send_callback!(sender, move || {
 								for &i in &copy {
-									let turf = unsafe { Value::turf_by_id_unchecked(i) };
-									if cfg!(target_os = "linux") {
-										turf.get(byond_string!("air"))?
-											.call("vv_react", &[&turf])?;
-									} else {
-										turf.get(byond_string!("air"))?.call("react", &[&turf])?;
-									}
+									react_turf(i)?;
 								}
 								Ok(Value::null())
-							}));
+							});
 						}
 					}
 				});
 		});
-		let _ = sender.try_send(Box::new(move || {
+		send_callback!(sender, move || {
 			for &i in &reacters {
-				let turf = unsafe { Value::turf_by_id_unchecked(i) };
-				if cfg!(target_os = "linux") {
-					turf.get(byond_string!("air"))?.call("vv_react", &[&turf])?;
-				} else {
-					turf.get(byond_string!("air"))?.call("react", &[&turf])?;
-				}
+				react_turf(i)?;
 			}
 			for &i in &visual_updaters {
 				let turf = unsafe { Value::turf_by_id_unchecked(i) };
 				turf.call("update_visuals", &[])?;
 			}
 			Ok(Value::null())
-		}));
+		});
 	});
 }
 
+// Expected function call: react_all_turfs(time_ms)
+// Reacts every turf that can react, independent of turf processing, for up to time_ms milliseconds.
+// Returns: TRUE if it ran out of time first, FALSE otherwise
+#[hook("/datum/controller/subsystem/air/proc/react_all_turfs")]
+fn _react_all_turfs_hook(time_val: Value) {
+	let start_time = Instant::now();
+	let budget = Duration::from_secs_f32(
+		time_val
+			.as_number()
+			.map_err(|_| {
+				runtime!(
+					"Attempt to interpret non-number value as number {} {}:{}",
+					std::file!(),
+					std::line!(),
+					std::column!()
+				)
+			})?
+			.max(0.0) / 1000.0,
+	);
+	let mut reacters = GasArena::with_all_mixtures(|all_mixtures| {
+		turf_gases()
+			.shards()
+			.par_iter()
+			.map(|shard| {
+				shard
+					.read()
+					.iter()
+					.filter(|(_, m_v)| m_v.get().enabled())
+					.filter_map(|(&i, m_v)| {
+						all_mixtures
+							.get(m_v.get().mix)
+							.and_then(RwLock::try_read)
+							.map_or(false, |gas| gas.can_react())
+							.then(|| i)
+					})
+					.collect::<Vec<_>>()
+			})
+			.flatten()
+			.collect::<Vec<_>>()
+	});
+	reacters.sort_unstable();
+	// start just past wherever the last sweep stopped, wrapping around to the turfs before it
+	let cursor = REACT_SWEEP_CURSOR.load(Ordering::Relaxed);
+	let resume_at = reacters.partition_point(|&i| i <= cursor);
+	reacters.rotate_left(resume_at);
+	let mut out_of_time = false;
+	for &i in &reacters {
+		if start_time.elapsed() >= budget {
+			out_of_time = true;
+			break;
+		}
+		react_turf(i)?;
+		mark_turf_processed(i);
+		REACT_SWEEP_CURSOR.store(i, Ordering::Relaxed);
+	}
+	if !out_of_time {
+		REACT_SWEEP_CURSOR.store(0, Ordering::Relaxed);
+	}
+	let bench = start_time.elapsed().as_millis();
+	let prev_cost = src
+		.get_number(byond_string!("cost_react_sweep"))
+		.unwrap_or_default();
+	src.set(
+		byond_string!("cost_react_sweep"),
+		Value::from(0.8 * prev_cost + 0.2 * (bench as f32)),
+	)?;
+	Ok(Value::from(out_of_time))
+}
+
 static HEAT_PROCESS_TIME: AtomicU64 = AtomicU64::new(1_000_000);
 
 #[hook("/datum/controller/subsystem/air/proc/heat_process_time")]
@@ -720,6 +1060,10 @@ fn _process_heat_time() {
 
 static PROCESSING_HEAT: AtomicBool = AtomicBool::new(false);
 
+pub(crate) fn heat_running() -> bool {
+	PROCESSING_HEAT.load(Ordering::Relaxed)
+}
+
 // Expected function call: process_turf_heat()
 // Returns: TRUE if thread not done, FALSE otherwise
 #[hook("/datum/controller/subsystem/air/proc/process_turf_heat")]
@@ -753,26 +1097,7 @@ fn _process_heat_hook() {
 				std::column!()
 			)
 		})? / 10.0) as f64;
-		let max_x = auxtools::Value::world()
-			.get_number(byond_string!("maxx"))
-			.map_err(|_| {
-				runtime!(
-					"Attempt to interpret non-number value as number {} {}:{}",
-					std::file!(),
-					std::line!(),
-					std::column!()
-				)
-			})? as i32;
-		let max_y = auxtools::Value::world()
-			.get_number(byond_string!("maxy"))
-			.map_err(|_| {
-				runtime!(
-					"Attempt to interpret non-number value as number {} {}:{}",
-					std::file!(),
-					std::line!(),
-					std::column!()
-				)
-			})? as i32;
+		let (max_x, max_y) = world_dimensions()?;
 		rayon::spawn(move || {
 			let start_time = Instant::now();
 			let sender = byond_callback_sender();
@@ -799,7 +1124,9 @@ fn _process_heat_hook() {
 								let mut heat_delta = 0.0;
 								let is_temp_delta_with_air = turf_gases()
 									.get(&i)
-									.filter(|m| m.simulation_level & SIMULATION_LEVEL_ANY > 0)
+									.filter(|m| {
+										m.simulation_level & SIMULATION_LEVEL_ANY > 0 && !m.frozen()
+									})
 									.and_then(|m| {
 										GasArena::with_all_mixtures(|all_mixtures| {
 											all_mixtures.get(m.mix).and_then(RwLock::try_read).map(
@@ -857,11 +1184,7 @@ fn _process_heat_hook() {
 					let t: &mut ThermalInfo = &mut turf_temperatures().get_mut(&i).unwrap();
 					t.temperature = turf_gases()
 						.get(&i)
-						.filter(|m| {
-							m.simulation_level != SIMULATION_LEVEL_NONE
-								&& m.simulation_level & SIMULATION_LEVEL_DISABLED
-									!= SIMULATION_LEVEL_DISABLED
-						})
+						.filter(|m| m.enabled())
 						.and_then(|m| {
 							GasArena::with_all_mixtures(|all_mixtures| {
 								all_mixtures.get(m.mix).map(|entry| {
@@ -882,17 +1205,17 @@ fn _process_heat_hook() {
 						})
 						.unwrap_or(new_temp);
 					if !t.temperature.is_normal() {
-						t.temperature = 2.7;
+						t.temperature = TCMB;
 					}
 					if t.temperature > MINIMUM_TEMPERATURE_START_SUPERCONDUCTION
 						&& t.temperature > t.heat_capacity
 					{
 						// not what heat capacity means but whatever
-						let _ = sender.try_send(Box::new(move || {
+						send_callback!(sender, move || {
 							let turf = unsafe { Value::turf_by_id_unchecked(i) };
 							turf.set(byond_string!("to_be_destroyed"), 1.0)?;
 							Ok(Value::null())
-						}));
+						});
 					}
 				});
 			PROCESSING_HEAT.store(false, Ordering::SeqCst);
@@ -923,4 +1246,5 @@ fn reset_auxmos_processing() {
 	PROCESSING_TURF_STEP.store(PROCESS_NOT_STARTED, Ordering::SeqCst);
 	PROCESSING_HEAT.store(false, Ordering::SeqCst);
 	HEAT_PROCESS_TIME.store(1_000_000, Ordering::SeqCst);
+	*SETTLE_COUNTS.write() = None;
 }
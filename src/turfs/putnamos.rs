@@ -49,7 +49,10 @@ fn explosively_depressurize(
 				if new_m.adjacency & bit == bit {
 					if let Some(adj) = turf_gases().get(&loc) {
 						let (&adj_i, &adj_m) = (adj.key(), adj.value());
-						turfs.push((adj_i, adj_m));
+						// Frozen turfs are held exactly as they are; decompression shouldn't reach into them either.
+						if !adj_m.frozen() {
+							turfs.push((adj_i, adj_m));
+						}
 					}
 				}
 			}
@@ -71,7 +74,8 @@ fn explosively_depressurize(
 		for (j, loc) in adjacent_tile_ids(m.adjacency, i, max_x, max_y) {
 			if let Some(adj) = turf_gases().get(&loc) {
 				let (adj_i, adj_m) = (*adj.key(), adj.value());
-				if !adjacency_info.contains_key(&adj_i) && !adj_m.is_immutable() {
+				if !adjacency_info.contains_key(&adj_i) && !adj_m.is_immutable() && !adj_m.frozen()
+				{
 					adjacency_info.insert(i, Cell::new((OPP_DIR_INDEX[j as usize], 0.0)));
 					unsafe { Value::turf_by_id_unchecked(adj_i) }
 						.set(byond_string!("pressure_specific_target"), &actual_turf)?;
@@ -136,6 +140,7 @@ fn explosively_depressurize(
 pub fn equalize(
 	equalize_turf_limit: usize,
 	equalize_hard_turf_limit: usize,
+	equalize_max_zone_turfs: usize,
 	max_x: i32,
 	max_y: i32,
 	high_pressure_turfs: BTreeSet<TurfID>,
@@ -155,7 +160,9 @@ pub fn equalize(
 			found_turfs.insert(initial_idx);
 			if GasMixtures::with_all_mixtures(|all_mixtures| {
 				// floodfill
-				while !border_turfs.is_empty() && turfs.len() < equalize_turf_limit {
+				while !border_turfs.is_empty()
+					&& turfs.len() < equalize_turf_limit.min(equalize_max_zone_turfs)
+				{
 					let (cur_idx, cur_turf, parent_turf, pressure_delta) =
 						border_turfs.pop_front().unwrap();
 					if let Some(our_gas_entry) = all_mixtures.get(cur_turf.mix) {
@@ -172,10 +179,13 @@ pub fn equalize(
 								}
 								found_turfs.insert(loc);
 								if let Some(adj_turf) = turf_gases().get(&loc) {
+									if adj_turf.frozen() {
+										continue;
+									}
 									if cfg!(feature = "putnamos_decompression")
 										&& adj_turf.is_immutable()
 									{
-										let _ = sender.try_send(Box::new(move || {
+										send_callback!(sender, move || {
 											explosively_depressurize(
 												cur_idx,
 												cur_turf,
@@ -183,7 +193,7 @@ pub fn equalize(
 												max_x,
 												max_y,
 											)
-										}));
+										});
 										return true;
 									} else {
 										let delta =
@@ -224,7 +234,7 @@ pub fn equalize(
 			});
 			for chunk_prelude in to_send.chunks(20) {
 				let chunk: Vec<_> = chunk_prelude.iter().copied().collect();
-				let _ = sender.try_send(Box::new(move || {
+				send_callback!(sender, move || {
 					for &(idx, parent, delta) in chunk.iter() {
 						if parent != 0 {
 							let turf = unsafe { Value::turf_by_id_unchecked(idx) };
@@ -236,7 +246,7 @@ pub fn equalize(
 						}
 					}
 					Ok(Value::null())
-				}));
+				});
 			}
 		}
 	}